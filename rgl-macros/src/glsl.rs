@@ -0,0 +1,81 @@
+use crate::naga_errors::parse_error_message;
+use naga::front::glsl::{Frontend, Options};
+use naga::valid::{Capabilities, ValidationFlags, Validator};
+use naga::ShaderStage;
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::path::PathBuf;
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, LitStr, Token};
+
+struct IncludeGlslInput {
+    path: LitStr,
+    stage: Option<Ident>,
+}
+
+impl Parse for IncludeGlslInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+
+        let stage = if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() { None } else { Some(input.parse()?) }
+        } else {
+            None
+        };
+
+        Ok(IncludeGlslInput { path, stage })
+    }
+}
+
+fn stage_from_name(name: &str) -> Option<ShaderStage> {
+    match name {
+        "vert" | "vertex" => Some(ShaderStage::Vertex),
+        "frag" | "fragment" => Some(ShaderStage::Fragment),
+        "comp" | "compute" => Some(ShaderStage::Compute),
+        _ => None,
+    }
+}
+
+pub(crate) fn include_glsl(input: TokenStream) -> syn::Result<TokenStream> {
+    let input: IncludeGlslInput = syn::parse2(input)?;
+
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| syn::Error::new(input.path.span(), "CARGO_MANIFEST_DIR is not set"))?;
+    let path = PathBuf::from(manifest_dir).join(input.path.value());
+
+    let stage = match input.stage.as_ref() {
+        Some(ident) => stage_from_name(&ident.to_string())
+            .ok_or_else(|| syn::Error::new(ident.span(), "expected `vertex`, `fragment` or `compute`"))?,
+        None => path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(stage_from_name)
+            .ok_or_else(|| {
+                syn::Error::new(input.path.span(), "cannot infer the shader stage, pass it as a second argument")
+            })?,
+    };
+
+    let source = std::fs::read_to_string(&path).map_err(|e| {
+        syn::Error::new(input.path.span(), format!("failed to read {}: {}", path.display(), e))
+    })?;
+
+    let module = Frontend::default()
+        .parse(&Options::from(stage), &source)
+        .map_err(|e| syn::Error::new(input.path.span(), parse_error_message(&e, &source)))?;
+
+    Validator::new(ValidationFlags::all(), Capabilities::all())
+        .validate(&module)
+        .map_err(|e| syn::Error::new(input.path.span(), e.emit_to_string(&source)))?;
+
+    // Referencing the file through include_bytes! makes cargo rebuild the
+    // caller when the shader changes.
+    let path_str = path.to_string_lossy();
+
+    Ok(quote! {
+        {
+            const _: &[u8] = include_bytes!(#path_str);
+            #source
+        }
+    })
+}
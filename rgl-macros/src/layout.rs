@@ -0,0 +1,104 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, Data, DeriveInput, Fields};
+
+pub(crate) struct Layout {
+    name: &'static str,
+    struct_alignment: usize,
+}
+
+pub(crate) const STD140: Layout = Layout { name: "Std140", struct_alignment: 16 };
+pub(crate) const STD430: Layout = Layout { name: "Std430", struct_alignment: 1 };
+
+pub(crate) fn derive_layout(input: TokenStream, layout: Layout) -> syn::Result<TokenStream> {
+    let mut input: DeriveInput = syn::parse2(input)?;
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().cloned().collect::<Vec<_>>(),
+            _ => return Err(syn::Error::new_spanned(&input.ident, "block layouts need named fields")),
+        },
+        _ => return Err(syn::Error::new_spanned(&input.ident, "block layouts can only be derived for structs")),
+    };
+
+    let lower = layout.name.to_lowercase();
+    let layout_trait = format_ident!("{}", layout.name);
+    let element_trait = format_ident!("{}Element", layout.name);
+    let write = format_ident!("write_{}", lower);
+    let read = format_ident!("read_{}", lower);
+    let members = format_ident!("{}_members", lower);
+    let struct_alignment = layout.struct_alignment;
+
+    let where_clause = input.generics.make_where_clause();
+    for field in fields.iter() {
+        let ty = &field.ty;
+        where_clause.predicates.push(parse_quote!(#ty: ::rgl::#layout_trait));
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let field_types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
+    let field_idents: Vec<_> = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+    let field_names: Vec<_> = field_idents.iter().map(|i| i.to_string()).collect();
+
+    Ok(quote! {
+        impl #impl_generics ::rgl::#layout_trait for #ident #ty_generics #where_clause {
+            const ALIGNMENT: usize = ::rgl::align_offset(
+                ::rgl::max_alignment(&[#(<#field_types as ::rgl::#layout_trait>::ALIGNMENT),*]),
+                #struct_alignment,
+            );
+
+            const SIZE: usize = {
+                let mut offset = 0;
+                #(
+                    offset = ::rgl::align_offset(offset, <#field_types as ::rgl::#layout_trait>::ALIGNMENT)
+                        + <#field_types as ::rgl::#layout_trait>::SIZE;
+                )*
+                ::rgl::align_offset(offset, <Self as ::rgl::#layout_trait>::ALIGNMENT)
+            };
+
+            fn #write(&self, bytes: &mut [u8]) {
+                let mut offset = 0;
+                #(
+                    offset = ::rgl::align_offset(offset, <#field_types as ::rgl::#layout_trait>::ALIGNMENT);
+                    ::rgl::#layout_trait::#write(&self.#field_idents, &mut bytes[offset..]);
+                    offset += <#field_types as ::rgl::#layout_trait>::SIZE;
+                )*
+                let _ = offset;
+            }
+
+            fn #read(bytes: &[u8]) -> Self {
+                let mut offset = 0;
+                let value = Self {
+                    #(
+                        #field_idents: {
+                            offset = ::rgl::align_offset(offset, <#field_types as ::rgl::#layout_trait>::ALIGNMENT);
+                            let field = <#field_types as ::rgl::#layout_trait>::#read(&bytes[offset..]);
+                            offset += <#field_types as ::rgl::#layout_trait>::SIZE;
+                            field
+                        },
+                    )*
+                };
+                let _ = offset;
+                value
+            }
+
+            fn #members(name: &str, base: usize, members: &mut Vec<::rgl::LayoutMember>) {
+                let mut offset = 0;
+                #(
+                    offset = ::rgl::align_offset(offset, <#field_types as ::rgl::#layout_trait>::ALIGNMENT);
+                    <#field_types as ::rgl::#layout_trait>::#members(
+                        &::rgl::member_name(name, #field_names),
+                        base + offset,
+                        members,
+                    );
+                    offset += <#field_types as ::rgl::#layout_trait>::SIZE;
+                )*
+                let _ = offset;
+            }
+        }
+
+        impl #impl_generics ::rgl::#element_trait for #ident #ty_generics #where_clause {}
+    })
+}
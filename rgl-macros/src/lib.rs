@@ -0,0 +1,43 @@
+extern crate proc_macro;
+
+#[cfg(feature = "glsl")]
+mod glsl;
+#[cfg(feature = "glsl")]
+mod naga_errors;
+#[cfg(feature = "derive")]
+mod layout;
+
+/// Embeds a GLSL file as a `&'static str` after checking it with naga.
+///
+/// `include_glsl!("shaders/basic.frag")` takes the stage from the file
+/// extension (`vert`, `frag` or `comp`), `include_glsl!("basic.glsl", vertex)`
+/// names it explicitly. Paths are relative to the crate's manifest directory.
+///
+/// naga only parses `#version 440` and later, so older shaders are rejected
+/// with an error saying so even if a driver would accept them.
+#[cfg(feature = "glsl")]
+#[proc_macro]
+pub fn include_glsl(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    glsl::include_glsl(input.into())
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Implements `rgl::Std140` for a struct, padding its fields the way a
+/// `layout(std140)` block lays out the matching GLSL struct.
+#[cfg(feature = "derive")]
+#[proc_macro_derive(Std140)]
+pub fn derive_std140(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    layout::derive_layout(input.into(), layout::STD140)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Implements `rgl::Std430` for a struct, see `Std140`.
+#[cfg(feature = "derive")]
+#[proc_macro_derive(Std430)]
+pub fn derive_std430(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    layout::derive_layout(input.into(), layout::STD430)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
@@ -0,0 +1,22 @@
+// Shared with rgl's `validate_glsl`, which includes this file, so the macro
+// and the runtime check describe naga's errors the same way.
+
+use naga::front::glsl::{ErrorKind, ParseErrors};
+
+// An older `#version` is a limit of naga, not a mistake in the shader, so it
+// gets its own message instead of naga's diagnostic.
+pub(crate) fn parse_error_message(errors: &ParseErrors, source: &str) -> String {
+    let version = errors.errors.iter().find_map(|e| match e.kind {
+        ErrorKind::InvalidVersion(version) => Some(version),
+        _ => None,
+    });
+
+    match version {
+        Some(version) => format!(
+            "`#version {}` is not supported by naga, which is used to check shaders \
+             without GL; it needs `#version 440` or later",
+            version
+        ),
+        None => errors.emit_to_string(source),
+    }
+}
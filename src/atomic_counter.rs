@@ -0,0 +1,88 @@
+use crate::{
+    get_named_buffer_sub_data, get_program_interface_iv, get_program_resource_iv, get_program_resource_name,
+    get_rgl_result, read_named_buffer, AtomicCounterBuffer, BufferObject, BufferUsage, Error, ProgramInterface,
+    ProgramInterfaceParameter, ProgramResourceProperty, RGLResult, ShaderProgram,
+};
+use gl::types::*;
+
+////////////////////////////////////////////////////////////////////
+
+/// An `atomic_uint` uniform. Its value lives at `offset` in the buffer
+/// bound to atomic counter binding point `binding`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AtomicCounter {
+    pub name: String,
+    pub binding: GLuint,
+    pub offset: GLuint,
+    pub array_size: GLint,
+}
+
+pub fn get_atomic_counters(program: &ShaderProgram) -> RGLResult<Vec<AtomicCounter>> {
+    use ProgramResourceProperty::*;
+
+    let count = get_program_interface_iv(program, ProgramInterface::Uniform, ProgramInterfaceParameter::ActiveResources)?;
+    let mut counters = vec![];
+
+    for index in 0..count.max(0) as GLuint {
+        let values = get_program_resource_iv(program, ProgramInterface::Uniform, index, &[AtomicCounterBufferIndex, Offset, ArraySize])?;
+
+        if values[0] < 0 {
+            continue;
+        }
+
+        let binding = get_program_resource_iv(program, ProgramInterface::AtomicCounterBuffer, values[0] as GLuint, &[BufferBinding])?[0];
+
+        counters.push(AtomicCounter {
+            name: get_program_resource_name(program, ProgramInterface::Uniform, index)?,
+            binding: binding as GLuint,
+            offset: values[1] as GLuint,
+            array_size: values[2],
+        });
+    }
+
+    counters.sort_by_key(|c| (c.binding, c.offset));
+
+    Ok(counters)
+}
+
+////////////////////////////////////////////////////////////////////
+
+/// Sets every counter in the buffer to `value`.
+pub fn reset_atomic_counters<U>(buffer: &BufferObject<AtomicCounterBuffer, u32, U>, value: u32) -> RGLResult<()> where U: BufferUsage {
+    use std::ffi::c_void;
+
+    unsafe {
+        gl::ClearNamedBufferData(
+            buffer.buffer_id,
+            gl::R32UI,
+            gl::RED_INTEGER,
+            gl::UNSIGNED_INT,
+            &value as *const u32 as *const c_void,
+        );
+    }
+
+    get_rgl_result(())
+}
+
+/// Reads every counter in the buffer. Counters incremented by shaders need
+/// a `memory_barrier` with `MemoryBarrier::BufferUpdate` first.
+pub fn read_atomic_counters<U>(buffer: &BufferObject<AtomicCounterBuffer, u32, U>) -> RGLResult<Vec<u32>> where U: BufferUsage {
+    read_named_buffer(buffer)
+}
+
+/// Reads the value of one element of `counter` from the buffer bound to
+/// its binding point.
+pub fn read_atomic_counter<U>(
+    buffer: &BufferObject<AtomicCounterBuffer, u32, U>,
+    counter: &AtomicCounter,
+    element: usize,
+) -> RGLResult<u32> where U: BufferUsage {
+    if element >= counter.array_size.max(1) as usize {
+        return Err(vec![Error::InvalidValue]);
+    }
+
+    let mut value = [0u32];
+    get_named_buffer_sub_data(buffer, counter.offset as usize / 4 + element, &mut value)?;
+
+    Ok(value[0])
+}
@@ -130,6 +130,26 @@ pub fn vertex_attrib_pointer<S, T>(
     get_rgl_result(())
 }
 
+/// Like `vertex_attrib_pointer` for `f64` data, but keeps the full precision
+/// for shader inputs declared as `double` or `dvec*` instead of converting
+/// to float.
+pub fn vertex_attrib_l_pointer<S>(index: GLuint) -> RGLResult<()> where S: AttributeVector {
+    use std::ptr;
+    use std::mem;
+
+    unsafe {
+        gl::VertexAttribLPointer(
+            index,
+            S::len(),
+            gl::DOUBLE,
+            mem::size_of::<f64>() as GLsizei * S::len(),
+            ptr::null(),
+        );
+    }
+
+    get_rgl_result(())
+}
+
 pub fn enable_vertex_attrib_array(index: GLuint) -> RGLResult<()> {
     unsafe {
         gl::EnableVertexAttribArray(index);
@@ -0,0 +1,425 @@
+use crate::BufferBlock;
+use gl::types::*;
+use std::convert::TryInto;
+
+#[cfg(feature = "derive")]
+pub use rgl_macros::{Std140, Std430};
+
+////////////////////////////////////////////////////////////////////
+
+pub const fn align_offset(offset: usize, alignment: usize) -> usize {
+    offset.div_ceil(alignment) * alignment
+}
+
+pub const fn max_alignment(alignments: &[usize]) -> usize {
+    let mut max = 1;
+    let mut i = 0;
+
+    while i < alignments.len() {
+        if alignments[i] > max {
+            max = alignments[i];
+        }
+        i += 1;
+    }
+
+    max
+}
+
+/// The name of `field` inside the member `name`, used by the derives.
+pub fn member_name(name: &str, field: &str) -> String {
+    if name.is_empty() {
+        field.to_owned()
+    } else {
+        format!("{}.{}", name, field)
+    }
+}
+
+/// A leaf member of a block as laid out on the Rust side, named the way
+/// GL reports block members.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayoutMember {
+    pub name: String,
+    pub offset: usize,
+    pub array_stride: Option<usize>,
+}
+
+/// A type with a std140 layout. `[T; 2]` to `[T; 4]` of scalars are
+/// vectors, `[[T; R]; C]` is a column major matrix, and arrays of scalars
+/// are written as `[Scalar<T>; N]`.
+pub trait Std140 {
+    const ALIGNMENT: usize;
+    const SIZE: usize;
+
+    /// Writes the value to the start of `bytes`, which holds at least
+    /// `SIZE` bytes.
+    fn write_std140(&self, bytes: &mut [u8]);
+
+    /// Reads a value from the start of `bytes`, which holds at least `SIZE`
+    /// bytes laid out as `write_std140` writes them.
+    fn read_std140(bytes: &[u8]) -> Self where Self: Sized;
+
+    fn std140_members(name: &str, offset: usize, members: &mut Vec<LayoutMember>) {
+        members.push(LayoutMember {
+            name: name.to_owned(),
+            offset,
+            array_stride: None,
+        });
+    }
+}
+
+/// A type with a std430 layout, see `Std140`.
+pub trait Std430 {
+    const ALIGNMENT: usize;
+    const SIZE: usize;
+
+    fn write_std430(&self, bytes: &mut [u8]);
+
+    fn read_std430(bytes: &[u8]) -> Self where Self: Sized;
+
+    fn std430_members(name: &str, offset: usize, members: &mut Vec<LayoutMember>) {
+        members.push(LayoutMember {
+            name: name.to_owned(),
+            offset,
+            array_stride: None,
+        });
+    }
+}
+
+/// Types that can be array elements in a std140 block. Bare scalars are
+/// left out so that `[f32; 4]` stays a vec4.
+pub trait Std140Element : Std140 {}
+
+/// Types that can be array elements in a std430 block.
+pub trait Std430Element : Std430 {}
+
+/// A scalar as an array element, for GLSL arrays like `float weights[8]`.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[repr(transparent)]
+pub struct Scalar<T>(pub T);
+
+////////////////////////////////////////////////////////////////////
+
+macro_rules! impl_layout_scalar {
+    ($scalar:ty, $size:expr) => {
+        impl_layout_scalar!(@layout $scalar, $size, Std140, write_std140, read_std140, Std140Element);
+        impl_layout_scalar!(@layout $scalar, $size, Std430, write_std430, read_std430, Std430Element);
+    };
+    (@layout $scalar:ty, $size:expr, $layout:ident, $write:ident, $read:ident, $element:ident) => {
+        impl $layout for $scalar {
+            const ALIGNMENT: usize = $size;
+            const SIZE: usize = $size;
+
+            fn $write(&self, bytes: &mut [u8]) {
+                bytes[..$size].copy_from_slice(&self.to_ne_bytes());
+            }
+
+            fn $read(bytes: &[u8]) -> Self {
+                <$scalar>::from_ne_bytes(bytes[..$size].try_into().unwrap())
+            }
+        }
+
+        impl_layout_scalar!(@vector $scalar, $size, $layout, $write, $read, $element, 2, 2);
+        impl_layout_scalar!(@vector $scalar, $size, $layout, $write, $read, $element, 3, 4);
+        impl_layout_scalar!(@vector $scalar, $size, $layout, $write, $read, $element, 4, 4);
+    };
+    (@vector $scalar:ty, $size:expr, $layout:ident, $write:ident, $read:ident, $element:ident, $n:expr, $align:expr) => {
+        impl $layout for [$scalar; $n] {
+            const ALIGNMENT: usize = $size * $align;
+            const SIZE: usize = $size * $n;
+
+            fn $write(&self, bytes: &mut [u8]) {
+                for (component, chunk) in self.iter().zip(bytes.chunks_exact_mut($size)) {
+                    chunk.copy_from_slice(&component.to_ne_bytes());
+                }
+            }
+
+            fn $read(bytes: &[u8]) -> Self {
+                std::array::from_fn(|i| <$scalar>::$read(&bytes[i * $size..]))
+            }
+        }
+
+        impl $element for [$scalar; $n] {}
+    };
+}
+
+impl_layout_scalar!(f32, 4);
+impl_layout_scalar!(i32, 4);
+impl_layout_scalar!(u32, 4);
+impl_layout_scalar!(f64, 8);
+
+// GLSL bools take up four bytes.
+macro_rules! impl_layout_bool {
+    ($layout:ident, $write:ident, $read:ident) => {
+        impl $layout for bool {
+            const ALIGNMENT: usize = 4;
+            const SIZE: usize = 4;
+
+            fn $write(&self, bytes: &mut [u8]) {
+                bytes[..4].copy_from_slice(&(*self as u32).to_ne_bytes());
+            }
+
+            fn $read(bytes: &[u8]) -> Self {
+                u32::$read(bytes) != 0
+            }
+        }
+    };
+}
+
+impl_layout_bool!(Std140, write_std140, read_std140);
+impl_layout_bool!(Std430, write_std430, read_std430);
+
+// Elements of leaf arrays are reported as one member with an array stride,
+// elements of struct arrays by the members of the first element.
+macro_rules! impl_layout_array {
+    ($layout:ident, $write:ident, $read:ident, $members:ident, $element:ident, $stride:expr, $align:expr) => {
+        impl<T> $layout for Scalar<T> where T: $layout {
+            const ALIGNMENT: usize = T::ALIGNMENT;
+            const SIZE: usize = T::SIZE;
+
+            fn $write(&self, bytes: &mut [u8]) {
+                self.0.$write(bytes)
+            }
+
+            fn $read(bytes: &[u8]) -> Self {
+                Scalar(T::$read(bytes))
+            }
+        }
+
+        impl<T> $element for Scalar<T> where T: $layout {}
+
+        impl<T, const N: usize> $layout for [T; N] where T: $element {
+            const ALIGNMENT: usize = $align;
+            const SIZE: usize = $stride * N;
+
+            fn $write(&self, bytes: &mut [u8]) {
+                for (i, element) in self.iter().enumerate() {
+                    element.$write(&mut bytes[i * $stride..]);
+                }
+            }
+
+            fn $read(bytes: &[u8]) -> Self {
+                std::array::from_fn(|i| T::$read(&bytes[i * $stride..]))
+            }
+
+            fn $members(name: &str, offset: usize, members: &mut Vec<LayoutMember>) {
+                let first = format!("{}[0]", name);
+                let mut element_members = vec![];
+
+                T::$members(&first, offset, &mut element_members);
+
+                if element_members.len() == 1 && element_members[0].name == first {
+                    members.push(LayoutMember {
+                        name: name.to_owned(),
+                        offset,
+                        array_stride: Some($stride),
+                    });
+                } else {
+                    members.extend(element_members);
+                }
+            }
+        }
+
+        impl<T, const N: usize> $element for [T; N] where T: $element {}
+    };
+}
+
+impl_layout_array!(
+    Std140,
+    write_std140,
+    read_std140,
+    std140_members,
+    Std140Element,
+    align_offset(align_offset(T::SIZE, T::ALIGNMENT), 16),
+    align_offset(T::ALIGNMENT, 16)
+);
+impl_layout_array!(
+    Std430,
+    write_std430,
+    read_std430,
+    std430_members,
+    Std430Element,
+    align_offset(T::SIZE, T::ALIGNMENT),
+    T::ALIGNMENT
+);
+
+////////////////////////////////////////////////////////////////////
+
+pub fn std140_bytes<T>(value: &T) -> Vec<u8> where T: Std140 {
+    let mut bytes = vec![0; T::SIZE];
+    value.write_std140(&mut bytes);
+    bytes
+}
+
+pub fn std430_bytes<T>(value: &T) -> Vec<u8> where T: Std430 {
+    let mut bytes = vec![0; T::SIZE];
+    value.write_std430(&mut bytes);
+    bytes
+}
+
+/// Decodes a `T` from bytes laid out by a `layout(std430)` block, such as
+/// the contents of a shader storage buffer. Returns `None` if `bytes` is
+/// shorter than `T::SIZE`.
+pub fn from_std430_bytes<T>(bytes: &[u8]) -> Option<T> where T: Std430 {
+    if bytes.len() < T::SIZE {
+        return None;
+    }
+
+    Some(T::read_std430(bytes))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LayoutMismatch {
+    Missing { name: String },
+    Offset { name: String, expected: usize, actual: GLint },
+    ArrayStride { name: String, expected: usize, actual: GLint },
+}
+
+fn check_layout(members: Vec<LayoutMember>, block: &BufferBlock) -> Result<(), Vec<LayoutMismatch>> {
+    let mut mismatches = vec![];
+
+    for member in members {
+        // Arrays are reported as `name[0]`, matrices as `name`.
+        let block_member = block
+            .member(&member.name)
+            .or_else(|| block.member(&format!("{}[0]", member.name)));
+
+        let block_member = match block_member {
+            Some(block_member) => block_member,
+            None => {
+                mismatches.push(LayoutMismatch::Missing { name: member.name });
+                continue;
+            }
+        };
+
+        if block_member.offset != member.offset as GLint {
+            mismatches.push(LayoutMismatch::Offset {
+                name: member.name.clone(),
+                expected: member.offset,
+                actual: block_member.offset,
+            });
+        }
+
+        let actual_stride = if block_member.array_stride > 0 {
+            block_member.array_stride
+        } else {
+            block_member.matrix_stride
+        };
+
+        if let Some(stride) = member.array_stride {
+            if actual_stride > 0 && actual_stride != stride as GLint {
+                mismatches.push(LayoutMismatch::ArrayStride {
+                    name: member.name,
+                    expected: stride,
+                    actual: actual_stride,
+                });
+            }
+        }
+    }
+
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(mismatches)
+    }
+}
+
+/// Compares the offsets of `T`'s members with the ones the linker chose for
+/// `block`, which has to be declared `layout(std140)`.
+pub fn check_std140_layout<T>(block: &BufferBlock) -> Result<(), Vec<LayoutMismatch>> where T: Std140 {
+    let mut members = vec![];
+    T::std140_members("", 0, &mut members);
+    check_layout(members, block)
+}
+
+pub fn check_std430_layout<T>(block: &BufferBlock) -> Result<(), Vec<LayoutMismatch>> where T: Std430 {
+    let mut members = vec![];
+    T::std430_members("", 0, &mut members);
+    check_layout(members, block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn std140_members<T>() -> Vec<LayoutMember> where T: Std140 {
+        let mut members = vec![];
+        T::std140_members("m", 0, &mut members);
+        members
+    }
+
+    fn std430_members<T>() -> Vec<LayoutMember> where T: Std430 {
+        let mut members = vec![];
+        T::std430_members("m", 0, &mut members);
+        members
+    }
+
+    fn stride(members: Vec<LayoutMember>) -> Option<usize> {
+        assert_eq!(members.len(), 1);
+        members[0].array_stride
+    }
+
+    #[test]
+    fn vectors() {
+        assert_eq!((<[f32; 2] as Std140>::ALIGNMENT, <[f32; 2] as Std140>::SIZE), (8, 8));
+        assert_eq!((<[f32; 3] as Std140>::ALIGNMENT, <[f32; 3] as Std140>::SIZE), (16, 12));
+        assert_eq!((<[f32; 3] as Std430>::ALIGNMENT, <[f32; 3] as Std430>::SIZE), (16, 12));
+        assert_eq!((<[f32; 4] as Std430>::ALIGNMENT, <[f32; 4] as Std430>::SIZE), (16, 16));
+        assert_eq!((<bool as Std140>::ALIGNMENT, <bool as Std140>::SIZE), (4, 4));
+    }
+
+    #[test]
+    fn double_vectors() {
+        assert_eq!((<[f64; 2] as Std430>::ALIGNMENT, <[f64; 2] as Std430>::SIZE), (16, 16));
+        assert_eq!((<[f64; 3] as Std140>::ALIGNMENT, <[f64; 3] as Std140>::SIZE), (32, 24));
+        assert_eq!((<[f64; 3] as Std430>::ALIGNMENT, <[f64; 3] as Std430>::SIZE), (32, 24));
+
+        assert_eq!(<[[f64; 3]; 2] as Std140>::SIZE, 64);
+        assert_eq!(stride(std140_members::<[[f64; 3]; 2]>()), Some(32));
+    }
+
+    #[test]
+    fn matrices() {
+        // mat3: three vec3 columns, each padded to a vec4.
+        assert_eq!((<[[f32; 3]; 3] as Std140>::ALIGNMENT, <[[f32; 3]; 3] as Std140>::SIZE), (16, 48));
+        assert_eq!((<[[f32; 3]; 3] as Std430>::ALIGNMENT, <[[f32; 3]; 3] as Std430>::SIZE), (16, 48));
+        assert_eq!(stride(std140_members::<[[f32; 3]; 3]>()), Some(16));
+
+        // mat2 columns are only rounded up to 16 bytes in std140.
+        assert_eq!(<[[f32; 2]; 2] as Std140>::SIZE, 32);
+        assert_eq!(<[[f32; 2]; 2] as Std430>::SIZE, 16);
+        assert_eq!(stride(std430_members::<[[f32; 2]; 2]>()), Some(8));
+    }
+
+    #[test]
+    fn scalar_arrays() {
+        assert_eq!((<[Scalar<f32>; 4] as Std140>::ALIGNMENT, <[Scalar<f32>; 4] as Std140>::SIZE), (16, 64));
+        assert_eq!((<[Scalar<f32>; 4] as Std430>::ALIGNMENT, <[Scalar<f32>; 4] as Std430>::SIZE), (4, 16));
+        assert_eq!(stride(std140_members::<[Scalar<f32>; 4]>()), Some(16));
+        assert_eq!(stride(std430_members::<[Scalar<f32>; 4]>()), Some(4));
+
+        assert_eq!(<[Scalar<f64>; 3] as Std140>::SIZE, 48);
+        assert_eq!(<[Scalar<f64>; 3] as Std430>::SIZE, 24);
+    }
+
+    #[test]
+    fn vec3_arrays() {
+        assert_eq!(<[[f32; 3]; 4] as Std140>::SIZE, 64);
+        assert_eq!(<[[f32; 3]; 4] as Std430>::SIZE, 64);
+        assert_eq!(stride(std430_members::<[[f32; 3]; 4]>()), Some(16));
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let weights = [Scalar(1.0f32), Scalar(2.0), Scalar(3.0)];
+
+        let bytes = std140_bytes(&weights);
+        assert_eq!(bytes.len(), 48);
+        assert_eq!(&bytes[16..20], &2.0f32.to_ne_bytes());
+        assert_eq!(<[Scalar<f32>; 3]>::read_std140(&bytes), weights);
+
+        let bytes = std430_bytes(&weights);
+        assert_eq!(bytes.len(), 12);
+        assert_eq!(from_std430_bytes::<[Scalar<f32>; 3]>(&bytes), Some(weights));
+        assert_eq!(from_std430_bytes::<[Scalar<f32>; 3]>(&bytes[..8]), None);
+    }
+}
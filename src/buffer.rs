@@ -1,5 +1,7 @@
 use crate::get_rgl_result;
+use crate::Error;
 use crate::RGLResult;
+use crate::Std430;
 use gl::types::*;
 use std::marker::PhantomData;
 
@@ -208,7 +210,7 @@ impl<T> BufferType<T> for ArrayBuffer<T> where T: private::ArrayBufferPermitted
     }
 }
 
-impl<T> BufferType<T> for AtomicCounterBuffer {
+impl BufferType<u32> for AtomicCounterBuffer {
     fn to_gl_code() -> GLenum {
         gl::ATOMIC_COUNTER_BUFFER
     }
@@ -288,6 +290,49 @@ impl<T> BufferType<T> for UniformBuffer {
 
 ////////////////////////////////////////////////////////////////////
 
+/// Buffer targets with indexed binding points, bound with
+/// `bind_buffer_base` and `bind_buffer_range`.
+pub trait IndexedBufferType<K> : BufferType<K> {
+    /// The alignment `bind_buffer_range` offsets must have.
+    fn offset_alignment() -> RGLResult<GLintptr>;
+}
+
+fn get_offset_alignment(pname: GLenum) -> RGLResult<GLintptr> {
+    let mut alignment: GLint = 0;
+
+    unsafe {
+        gl::GetIntegerv(pname, &mut alignment);
+    }
+
+    get_rgl_result(alignment.max(1) as GLintptr)
+}
+
+impl IndexedBufferType<u32> for AtomicCounterBuffer {
+    fn offset_alignment() -> RGLResult<GLintptr> {
+        Ok(4)
+    }
+}
+
+impl<T> IndexedBufferType<T> for ShaderStorageBuffer {
+    fn offset_alignment() -> RGLResult<GLintptr> {
+        get_offset_alignment(gl::SHADER_STORAGE_BUFFER_OFFSET_ALIGNMENT)
+    }
+}
+
+impl<T> IndexedBufferType<T> for TransformFeedbackBuffer {
+    fn offset_alignment() -> RGLResult<GLintptr> {
+        Ok(4)
+    }
+}
+
+impl<T> IndexedBufferType<T> for UniformBuffer {
+    fn offset_alignment() -> RGLResult<GLintptr> {
+        get_offset_alignment(gl::UNIFORM_BUFFER_OFFSET_ALIGNMENT)
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+
 pub trait BufferUsage : private::PrivBufferUsage {
     fn to_gl_code() -> GLenum;
 }
@@ -407,7 +452,7 @@ pub fn named_buffer_data<T, K, U>(buffer: &BufferObject<T, K, U>, data: &[K]) ->
     unsafe {
         gl::NamedBufferData(
             buffer.buffer_id,
-            (data.len() * mem::size_of::<K>()) as isize,
+            mem::size_of_val(data) as isize,
             &data[0] as *const K as *const c_void,
             U::to_gl_code()
         );
@@ -416,4 +461,112 @@ pub fn named_buffer_data<T, K, U>(buffer: &BufferObject<T, K, U>, data: &[K]) ->
     get_rgl_result(())
 }
 
+pub fn bind_buffer_base<T, K, U>(index: GLuint, buffer_option: Option<&BufferObject<T, K, U>>) -> RGLResult<()> where T: IndexedBufferType<K>, U: BufferUsage {
+    let buffer_id = buffer_option.map_or(0, |buffer| buffer.buffer_id);
+
+    unsafe {
+        gl::BindBufferBase(T::to_gl_code(), index, buffer_id);
+    }
+
+    get_rgl_result(())
+}
+
+pub fn bind_buffer_range<T, K, U>(
+    index: GLuint,
+    buffer: &BufferObject<T, K, U>,
+    offset: GLintptr,
+    size: GLsizeiptr,
+) -> RGLResult<()> where T: IndexedBufferType<K>, U: BufferUsage {
+    if offset < 0 || size <= 0 || offset % T::offset_alignment()? != 0 {
+        return Err(vec![Error::InvalidValue]);
+    }
+
+    unsafe {
+        gl::BindBufferRange(T::to_gl_code(), index, buffer.buffer_id, offset, size);
+    }
+
+    get_rgl_result(())
+}
+
+////////////////////////////////////////////////////////////////////
+
+/// Element types that can be read back from a buffer.
+///
+/// # Safety
+///
+/// Every bit pattern of the type's size has to be a valid value, and the
+/// type must have no padding.
+pub unsafe trait BufferReadable : Copy {}
+
+unsafe impl BufferReadable for u8 {}
+unsafe impl BufferReadable for i8 {}
+unsafe impl BufferReadable for u16 {}
+unsafe impl BufferReadable for i16 {}
+unsafe impl BufferReadable for u32 {}
+unsafe impl BufferReadable for i32 {}
+unsafe impl BufferReadable for u64 {}
+unsafe impl BufferReadable for i64 {}
+unsafe impl BufferReadable for f32 {}
+unsafe impl BufferReadable for f64 {}
+unsafe impl<T, const N: usize> BufferReadable for [T; N] where T: BufferReadable {}
+
+pub fn get_named_buffer_size<T, K, U>(buffer: &BufferObject<T, K, U>) -> RGLResult<usize> where T: BufferType<K>, U: BufferUsage {
+    let mut size: GLint64 = 0;
+
+    unsafe {
+        gl::GetNamedBufferParameteri64v(buffer.buffer_id, gl::BUFFER_SIZE, &mut size);
+    }
+
+    get_rgl_result(size.max(0) as usize)
+}
+
+/// Reads `data.len()` elements starting at element `offset`. Writes made by
+/// shaders need a `memory_barrier` with `MemoryBarrier::BufferUpdate` first.
+pub fn get_named_buffer_sub_data<T, K, U>(buffer: &BufferObject<T, K, U>, offset: usize, data: &mut [K]) -> RGLResult<()> where T: BufferType<K>, K: BufferReadable, U: BufferUsage {
+    use std::mem;
+    use std::ffi::c_void;
+
+    let element_size = mem::size_of::<K>();
+
+    let end = offset
+        .checked_add(data.len())
+        .and_then(|end| end.checked_mul(element_size))
+        .ok_or_else(|| vec![Error::InvalidValue])?;
+
+    if end > get_named_buffer_size(buffer)? {
+        return Err(vec![Error::InvalidValue]);
+    }
+
+    unsafe {
+        gl::GetNamedBufferSubData(
+            buffer.buffer_id,
+            (offset * element_size) as GLintptr,
+            mem::size_of_val(data) as GLsizeiptr,
+            data.as_mut_ptr() as *mut c_void
+        );
+    }
+
+    get_rgl_result(())
+}
+
+/// Reads every whole element stored in the buffer.
+pub fn read_named_buffer<T, K, U>(buffer: &BufferObject<T, K, U>) -> RGLResult<Vec<K>> where T: BufferType<K>, K: BufferReadable + Default, U: BufferUsage {
+    let len = get_named_buffer_size(buffer)? / std::mem::size_of::<K>().max(1);
+    let mut data = vec![K::default(); len];
+
+    get_named_buffer_sub_data(buffer, 0, &mut data)?;
+
+    Ok(data)
+}
+
+/// Reads a `V` laid out as a `layout(std430)` block starting at byte
+/// `offset`, e.g. the results a compute shader wrote to a storage buffer.
+pub fn read_named_buffer_std430<V, T, U>(buffer: &BufferObject<T, u8, U>, offset: usize) -> RGLResult<V> where V: Std430, T: BufferType<u8>, U: BufferUsage {
+    let mut bytes = vec![0; V::SIZE];
+
+    get_named_buffer_sub_data(buffer, offset, &mut bytes)?;
+
+    Ok(V::read_std430(&bytes))
+}
+
 ////////////////////////////////////////////////////////////////////
@@ -0,0 +1,69 @@
+use crate::{get_rgl_result, RGLResult};
+use gl::types::*;
+
+////////////////////////////////////////////////////////////////////
+
+/// Runs the compute shader of the current program over a grid of work
+/// groups.
+pub fn dispatch_compute(num_groups_x: GLuint, num_groups_y: GLuint, num_groups_z: GLuint) -> RGLResult<()> {
+    unsafe {
+        gl::DispatchCompute(num_groups_x, num_groups_y, num_groups_z);
+    }
+
+    get_rgl_result(())
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MemoryBarrier {
+    VertexAttribArray,
+    ElementArray,
+    Uniform,
+    TextureFetch,
+    ShaderImageAccess,
+    Command,
+    PixelBuffer,
+    TextureUpdate,
+    BufferUpdate,
+    ClientMappedBuffer,
+    Framebuffer,
+    TransformFeedback,
+    AtomicCounter,
+    ShaderStorage,
+    QueryBuffer,
+    All,
+}
+
+impl MemoryBarrier {
+    fn to_gl_code(self) -> GLbitfield {
+        match self {
+            MemoryBarrier::VertexAttribArray => gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT,
+            MemoryBarrier::ElementArray => gl::ELEMENT_ARRAY_BARRIER_BIT,
+            MemoryBarrier::Uniform => gl::UNIFORM_BARRIER_BIT,
+            MemoryBarrier::TextureFetch => gl::TEXTURE_FETCH_BARRIER_BIT,
+            MemoryBarrier::ShaderImageAccess => gl::SHADER_IMAGE_ACCESS_BARRIER_BIT,
+            MemoryBarrier::Command => gl::COMMAND_BARRIER_BIT,
+            MemoryBarrier::PixelBuffer => gl::PIXEL_BUFFER_BARRIER_BIT,
+            MemoryBarrier::TextureUpdate => gl::TEXTURE_UPDATE_BARRIER_BIT,
+            MemoryBarrier::BufferUpdate => gl::BUFFER_UPDATE_BARRIER_BIT,
+            MemoryBarrier::ClientMappedBuffer => gl::CLIENT_MAPPED_BUFFER_BARRIER_BIT,
+            MemoryBarrier::Framebuffer => gl::FRAMEBUFFER_BARRIER_BIT,
+            MemoryBarrier::TransformFeedback => gl::TRANSFORM_FEEDBACK_BARRIER_BIT,
+            MemoryBarrier::AtomicCounter => gl::ATOMIC_COUNTER_BARRIER_BIT,
+            MemoryBarrier::ShaderStorage => gl::SHADER_STORAGE_BARRIER_BIT,
+            MemoryBarrier::QueryBuffer => gl::QUERY_BUFFER_BARRIER_BIT,
+            MemoryBarrier::All => gl::ALL_BARRIER_BITS,
+        }
+    }
+}
+
+/// Orders shader writes before the later accesses named by `barriers`, for
+/// example `MemoryBarrier::BufferUpdate` before reading a storage buffer back.
+pub fn memory_barrier(barriers: &[MemoryBarrier]) -> RGLResult<()> {
+    let bits = barriers.iter().fold(0, |bits, barrier| bits | barrier.to_gl_code());
+
+    unsafe {
+        gl::MemoryBarrier(bits);
+    }
+
+    get_rgl_result(())
+}
@@ -0,0 +1,278 @@
+////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+    Info,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShaderDiagnostic {
+    pub severity: DiagnosticSeverity,
+    pub source_index: Option<usize>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct InfoLog {
+    pub raw: String,
+    pub diagnostics: Vec<ShaderDiagnostic>,
+}
+
+impl InfoLog {
+    pub fn parse(raw: String) -> InfoLog {
+        let diagnostics = raw.lines().filter_map(parse_diagnostic).collect();
+
+        InfoLog { raw, diagnostics }
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &ShaderDiagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == DiagnosticSeverity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &ShaderDiagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == DiagnosticSeverity::Warning)
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+
+pub(crate) struct LogLocation {
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+    pub(crate) source: usize,
+    pub(crate) line: usize,
+}
+
+const SEVERITY_PREFIXES: [(&str, DiagnosticSeverity); 2] = [
+    ("ERROR: ", DiagnosticSeverity::Error),
+    ("WARNING: ", DiagnosticSeverity::Warning),
+];
+
+const SEVERITY_WORDS: [(&str, DiagnosticSeverity); 2] = [
+    ("error", DiagnosticSeverity::Error),
+    ("warning", DiagnosticSeverity::Warning),
+];
+
+// Mesa writes `preprocessor error`, NVIDIA `fatal error`.
+const SEVERITY_QUALIFIERS: [&str; 2] = ["preprocessor ", "fatal "];
+
+// Recognises the `source:line` prefix written by Mesa (`0:12(5): error: ..`)
+// and AMD (`ERROR: 0:12: ..`) and NVIDIA's `source(line)` (`0(12) : error ..`).
+pub(crate) fn find_log_location(log_line: &str) -> Option<LogLocation> {
+    let start = SEVERITY_PREFIXES
+        .iter()
+        .find(|(prefix, _)| log_line.starts_with(prefix))
+        .map_or(0, |(prefix, _)| prefix.len());
+
+    let bytes = log_line.as_bytes();
+    let source_end = start + count_digits(&bytes[start..]);
+
+    if source_end == start || source_end >= bytes.len() {
+        return None;
+    }
+
+    let line_start = source_end + 1;
+    let line_end = line_start + count_digits(&bytes[line_start..]);
+
+    if line_end == line_start {
+        return None;
+    }
+
+    let end = match bytes[source_end] {
+        b':' => line_end,
+        b'(' if bytes.get(line_end) == Some(&b')') => line_end + 1,
+        _ => return None,
+    };
+
+    Some(LogLocation {
+        start,
+        end,
+        source: log_line[start..source_end].parse().ok()?,
+        line: log_line[line_start..line_end].parse().ok()?,
+    })
+}
+
+fn count_digits(bytes: &[u8]) -> usize {
+    bytes.iter().take_while(|b| b.is_ascii_digit()).count()
+}
+
+fn parse_diagnostic(log_line: &str) -> Option<ShaderDiagnostic> {
+    let log_line = log_line.trim_end();
+
+    let prefix_severity = SEVERITY_PREFIXES
+        .iter()
+        .find(|(prefix, _)| log_line.starts_with(prefix))
+        .map(|(_, severity)| *severity);
+
+    let (source_index, line, mut rest) = if let Some(location) = find_log_location(log_line) {
+        (Some(location.source), Some(location.line), &log_line[location.end..])
+    } else if let Some(severity) = prefix_severity {
+        let message = log_line.split_once(": ").map_or("", |(_, message)| message);
+
+        // AMD closes its logs with "ERROR: N compilation errors.  No code generated."
+        if message.ends_with("No code generated.") {
+            return None;
+        }
+
+        return Some(ShaderDiagnostic {
+            severity,
+            source_index: None,
+            line: None,
+            column: None,
+            message: message.trim().to_string(),
+        });
+    } else {
+        (None, None, log_line)
+    };
+
+    // Mesa follows the line with a column in parentheses.
+    let mut column = None;
+
+    if let Some(after_paren) = rest.strip_prefix('(') {
+        let digits = count_digits(after_paren.as_bytes());
+
+        if digits > 0 && after_paren[digits..].starts_with(')') {
+            column = after_paren[..digits].parse().ok();
+            rest = &after_paren[digits + 1..];
+        }
+    }
+
+    let rest = rest.trim_start_matches(|c: char| c == ':' || c.is_whitespace());
+
+    let (severity, message) = match prefix_severity {
+        Some(severity) => (severity, rest),
+        None => {
+            let unqualified = SEVERITY_QUALIFIERS
+                .iter()
+                .find_map(|qualifier| rest.strip_prefix(qualifier))
+                .unwrap_or(rest);
+
+            let (severity, message) = SEVERITY_WORDS
+                .iter()
+                .find_map(|(word, severity)| {
+                    unqualified.strip_prefix(word)
+                        .filter(|message| message.starts_with([':', ' ']))
+                        .map(|message| (*severity, message))
+                })
+                .or_else(|| {
+                    // Lines without any location or severity are driver chatter.
+                    source_index.map(|_| (DiagnosticSeverity::Info, rest))
+                })?;
+
+            (severity, message.trim_start_matches(|c: char| c == ':' || c.is_whitespace()))
+        }
+    };
+
+    Some(ShaderDiagnostic {
+        severity,
+        source_index,
+        line,
+        column,
+        message: message.trim().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(severity: DiagnosticSeverity, line: usize, column: Option<usize>, message: &str) -> ShaderDiagnostic {
+        ShaderDiagnostic {
+            severity,
+            source_index: Some(0),
+            line: Some(line),
+            column,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn find_log_location_formats() {
+        let mesa = find_log_location("0:12(5): error: `color' undeclared").unwrap();
+        assert_eq!((mesa.start, mesa.end, mesa.source, mesa.line), (0, 4, 0, 12));
+
+        let nvidia = find_log_location("0(12) : error C1008: undefined variable \"color\"").unwrap();
+        assert_eq!((nvidia.start, nvidia.end, nvidia.source, nvidia.line), (0, 5, 0, 12));
+
+        let amd = find_log_location("ERROR: 2:7: 'color' : undeclared identifier").unwrap();
+        assert_eq!((amd.start, amd.end, amd.source, amd.line), (7, 10, 2, 7));
+
+        assert!(find_log_location("ERROR: 1 compilation errors.  No code generated.").is_none());
+        assert!(find_log_location("0(12 : error").is_none());
+        assert!(find_log_location("Vertex shader failed to compile.").is_none());
+    }
+
+    #[test]
+    fn parse_mesa() {
+        let log = InfoLog::parse(
+            "0:12(5): error: `color' undeclared\n\
+             0:3(1): warning: extension `GL_foo' unsupported\n\
+             0:3(1): preprocessor error: Invalid tokens after #\n"
+                .to_string(),
+        );
+
+        assert_eq!(
+            log.diagnostics,
+            vec![
+                diagnostic(DiagnosticSeverity::Error, 12, Some(5), "`color' undeclared"),
+                diagnostic(DiagnosticSeverity::Warning, 3, Some(1), "extension `GL_foo' unsupported"),
+                diagnostic(DiagnosticSeverity::Error, 3, Some(1), "Invalid tokens after #"),
+            ]
+        );
+        assert_eq!(log.errors().count(), 2);
+    }
+
+    #[test]
+    fn parse_nvidia() {
+        let log = InfoLog::parse(
+            "0(12) : error C1008: undefined variable \"color\"\n\
+             0(3) : warning C7022: unrecognized profile specifier \"foo\"\n\
+             0(7) : fatal error C9999: unexpected end of file\n"
+                .to_string(),
+        );
+
+        assert_eq!(
+            log.diagnostics,
+            vec![
+                diagnostic(DiagnosticSeverity::Error, 12, None, "C1008: undefined variable \"color\""),
+                diagnostic(DiagnosticSeverity::Warning, 3, None, "C7022: unrecognized profile specifier \"foo\""),
+                diagnostic(DiagnosticSeverity::Error, 7, None, "C9999: unexpected end of file"),
+            ]
+        );
+        assert_eq!(log.errors().count(), 2);
+    }
+
+    #[test]
+    fn parse_amd() {
+        let log = InfoLog::parse(
+            "WARNING: 0:4: 'lowp' : precision qualifier ignored\n\
+             ERROR: 0:12: 'color' : undeclared identifier\n\
+             ERROR: 1 compilation errors.  No code generated.\n"
+                .to_string(),
+        );
+
+        assert_eq!(
+            log.diagnostics,
+            vec![
+                diagnostic(DiagnosticSeverity::Warning, 4, None, "'lowp' : precision qualifier ignored"),
+                diagnostic(DiagnosticSeverity::Error, 12, None, "'color' : undeclared identifier"),
+            ]
+        );
+        assert_eq!(log.errors().count(), 1);
+        assert_eq!(log.warnings().count(), 1);
+    }
+
+    #[test]
+    fn parse_skips_chatter() {
+        let log = InfoLog::parse("Vertex shader failed to compile with the following errors:\n".to_string());
+        assert!(log.diagnostics.is_empty());
+    }
+}
@@ -1,5 +1,5 @@
 use gl::types::*;
-use crate::{Primitive,  RGLResult, get_rgl_result};
+use crate::{Primitive,  RGLResult, get_rgl_result, TransformFeedback};
 
 pub fn draw_arrays<P>(first: GLint, count: GLsizei) -> RGLResult<()> where P: Primitive {
     unsafe {
@@ -58,3 +58,41 @@ pub fn draw_elements<P, T>(count: GLsizei, indices: Option<&[T]>) -> RGLResult<(
 
     get_rgl_result(())
 }
+
+//////////////////////////////////////////////////////////////////////
+
+pub fn draw_transform_feedback<P>(tf: &TransformFeedback) -> RGLResult<()> where P: Primitive {
+    unsafe {
+        gl::DrawTransformFeedback(P::to_gl_code(), tf.tf_id);
+    }
+
+    get_rgl_result(())
+}
+
+pub fn draw_transform_feedback_stream<P>(tf: &TransformFeedback, stream: GLuint) -> RGLResult<()> where P: Primitive {
+    unsafe {
+        gl::DrawTransformFeedbackStream(P::to_gl_code(), tf.tf_id, stream);
+    }
+
+    get_rgl_result(())
+}
+
+pub fn draw_transform_feedback_instanced<P>(tf: &TransformFeedback, instance_count: GLsizei) -> RGLResult<()> where P: Primitive {
+    unsafe {
+        gl::DrawTransformFeedbackInstanced(P::to_gl_code(), tf.tf_id, instance_count);
+    }
+
+    get_rgl_result(())
+}
+
+pub fn draw_transform_feedback_stream_instanced<P>(
+    tf: &TransformFeedback,
+    stream: GLuint,
+    instance_count: GLsizei,
+) -> RGLResult<()> where P: Primitive {
+    unsafe {
+        gl::DrawTransformFeedbackStreamInstanced(P::to_gl_code(), tf.tf_id, stream, instance_count);
+    }
+
+    get_rgl_result(())
+}
@@ -0,0 +1,86 @@
+use crate::ShaderType;
+use naga_errors::parse_error_message;
+use gl::types::*;
+use naga::front::glsl::{Frontend, Options};
+use naga::valid::{Capabilities, ValidationFlags, Validator};
+use naga::ShaderStage;
+
+pub use rgl_macros::include_glsl;
+
+#[path = "../rgl-macros/src/naga_errors.rs"]
+mod naga_errors;
+
+#[derive(Clone, Debug)]
+pub enum GlslValidationError {
+    UnsupportedStage(GLenum),
+    Parse(String),
+    Validation(String),
+}
+
+fn naga_stage(stage: GLenum) -> Option<ShaderStage> {
+    match stage {
+        gl::VERTEX_SHADER => Some(ShaderStage::Vertex),
+        gl::FRAGMENT_SHADER => Some(ShaderStage::Fragment),
+        gl::COMPUTE_SHADER => Some(ShaderStage::Compute),
+        _ => None,
+    }
+}
+
+/// Parses and validates `source` with naga, without a GL context. naga's
+/// GLSL frontend only accepts `#version 440` and later with explicit
+/// `layout(binding = ..)` on uniforms.
+pub fn validate_glsl<T>(source: &str) -> Result<(), GlslValidationError> where T: ShaderType {
+    let stage = naga_stage(T::to_gl_code())
+        .ok_or_else(|| GlslValidationError::UnsupportedStage(T::to_gl_code()))?;
+
+    let module = Frontend::default()
+        .parse(&Options::from(stage), source)
+        .map_err(|e| GlslValidationError::Parse(parse_error_message(&e, source)))?;
+
+    Validator::new(ValidationFlags::all(), Capabilities::all())
+        .validate(&module)
+        .map_err(|e| GlslValidationError::Validation(e.emit_to_string(source)))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FragmentShaderType;
+
+    const VALID: &str = "#version 450
+layout(location = 0) out vec4 color;
+
+void main() {
+    color = vec4(1.0, 0.5, 0.25, 1.0);
+}
+";
+
+    #[test]
+    fn valid_shader() {
+        assert!(validate_glsl::<FragmentShaderType>(VALID).is_ok());
+    }
+
+    #[test]
+    fn undeclared_identifier() {
+        let source = VALID.replace("vec4(1.0, 0.5, 0.25, 1.0)", "tint");
+
+        assert!(matches!(
+            validate_glsl::<FragmentShaderType>(&source),
+            Err(GlslValidationError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let source = VALID.replace("#version 450", "#version 330 core");
+
+        match validate_glsl::<FragmentShaderType>(&source) {
+            Err(GlslValidationError::Parse(message)) => {
+                assert!(message.contains("`#version 330` is not supported by naga"), "{}", message);
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+}
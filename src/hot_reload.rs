@@ -0,0 +1,109 @@
+use crate::program_builder::StageKind;
+use crate::{CompileShaderError, Error, LinkProgramError, ProgramBuildError, ProgramBuilder, ShaderProgram, ShaderType};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub enum HotReloadError {
+    Io(PathBuf, io::Error),
+    Compile(Vec<(PathBuf, CompileShaderError)>),
+    Link(LinkProgramError),
+    Standard(Vec<Error>),
+}
+
+struct WatchedStage {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    kind: StageKind,
+}
+
+/// A program rebuilt from shader files whenever one of them changes on disk.
+/// The files are polled rather than watched from another thread, since the
+/// rebuild has to happen on the thread that owns the GL context.
+pub struct HotReloadProgram {
+    stages: Vec<WatchedStage>,
+    program: Option<ShaderProgram>,
+}
+
+impl HotReloadProgram {
+    pub fn new() -> HotReloadProgram {
+        HotReloadProgram {
+            stages: vec![],
+            program: None,
+        }
+    }
+
+    pub fn stage<T, P>(mut self, path: P) -> HotReloadProgram where T: ShaderType + 'static, P: Into<PathBuf> {
+        self.stages.push(WatchedStage {
+            path: path.into(),
+            modified: None,
+            kind: StageKind::of::<T>(),
+        });
+
+        self
+    }
+
+    /// The last program that compiled and linked, if any.
+    pub fn program(&self) -> Option<&ShaderProgram> {
+        self.program.as_ref()
+    }
+
+    /// Rebuilds the program unconditionally. On failure the previous program
+    /// stays active.
+    pub fn reload(&mut self) -> Result<(), HotReloadError> {
+        let mut builder = ProgramBuilder::new();
+
+        for stage in self.stages.iter_mut() {
+            stage.modified = modified_time(&stage.path);
+
+            let source = fs::read_to_string(&stage.path)
+                .map_err(|e| HotReloadError::Io(stage.path.clone(), e))?;
+
+            builder.push_stage(stage.kind, source);
+        }
+
+        let program = builder.build().map_err(|e| match e {
+            ProgramBuildError::Compile(errors) => HotReloadError::Compile(
+                errors
+                    .into_iter()
+                    .map(|e| (self.stages[e.index].path.clone(), e.error))
+                    .collect(),
+            ),
+            ProgramBuildError::Link(e) => HotReloadError::Link(e),
+            ProgramBuildError::Standard(e) => HotReloadError::Standard(e),
+        })?;
+
+        self.program = Some(program);
+
+        Ok(())
+    }
+
+    /// Checks the watched files and rebuilds the program if any of them
+    /// changed. Returns `None` when nothing changed.
+    pub fn poll(&mut self) -> Option<Result<(), HotReloadError>> {
+        let changed = self
+            .stages
+            .iter()
+            .any(|stage| modified_time(&stage.path) != stage.modified);
+
+        if changed {
+            Some(self.reload())
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for HotReloadProgram {
+    fn default() -> HotReloadProgram {
+        HotReloadProgram::new()
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
@@ -2,24 +2,72 @@ extern crate gl;
 
 use gl::types::*;
 
+mod atomic_counter;
 mod attributes;
+mod block_layout;
+mod diagnostics;
 mod draw;
+#[cfg(feature = "glsl-validation")]
+mod glsl_validation;
+#[cfg(feature = "hot-reload")]
+mod hot_reload;
 mod buffer;
+mod compute;
+mod reflection;
 mod shader;
 mod uniform;
+mod uniform_scan;
+mod permutation;
+mod preprocess;
 mod primitive;
-
+mod program_binary;
+mod program_builder;
+mod program_interface;
+mod shader_storage;
+mod spirv;
+mod subroutine;
+mod transform_feedback;
+
+pub use atomic_counter::*;
 pub use attributes::*;
+pub use block_layout::*;
+pub use diagnostics::*;
 pub use draw::*;
+#[cfg(feature = "glsl-validation")]
+pub use glsl_validation::*;
+#[cfg(feature = "hot-reload")]
+pub use hot_reload::*;
 pub use buffer::*;
+pub use compute::*;
+pub use reflection::*;
 pub use shader::*;
 pub use uniform::*;
+pub(crate) use uniform_scan::*;
+pub use permutation::*;
+pub use preprocess::*;
 pub use primitive::*;
+pub use program_binary::*;
+pub use program_builder::*;
+pub use program_interface::*;
+pub use shader_storage::*;
+pub use spirv::*;
+pub use subroutine::*;
+pub use transform_feedback::*;
 
 pub type RGLResult<T> = Result<T, Vec<Error>>;
 
 ///////////////////////////////////////////////////////////////////////////////
 
+/// Loads the GL functions through `loader`. Use this instead of
+/// `gl::load_with`, which does not load glSpecializeShader, so
+/// `specialize_shader` would always fail.
+pub fn load_with<F>(mut loader: F) where F: FnMut(&'static str) -> *const std::ffi::c_void {
+    gl::load_with(&mut loader);
+    spirv::load_with(&mut loader);
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
 pub enum BufferType {
     ColorBuffer,
     DepthBuffer,
@@ -54,6 +102,48 @@ pub fn clear(buffer: BufferType) {
 
 ////////////////////////////////////////////////////
 
+#[derive(Copy, Clone, Debug)]
+pub enum StringName {
+    Vendor,
+    Renderer,
+    Version,
+    ShadingLanguageVersion,
+}
+
+impl StringName {
+    fn to_gl_code(self) -> GLenum {
+        match self {
+            StringName::Vendor => gl::VENDOR,
+            StringName::Renderer => gl::RENDERER,
+            StringName::Version => gl::VERSION,
+            StringName::ShadingLanguageVersion => gl::SHADING_LANGUAGE_VERSION,
+        }
+    }
+}
+
+pub fn get_string(name: StringName) -> RGLResult<String> {
+    use std::ffi::CStr;
+
+    let ptr = unsafe { gl::GetString(name.to_gl_code()) };
+
+    let errors = get_errors_unchecked();
+
+    if ptr.is_null() {
+        if errors.is_empty() {
+            return Err(vec![Error::InvalidValue]);
+        }
+        return Err(errors);
+    }
+
+    let value = unsafe { CStr::from_ptr(ptr as *const GLchar) }
+        .to_string_lossy()
+        .into_owned();
+
+    make_rgl_result(value, errors)
+}
+
+////////////////////////////////////////////////////
+
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum Error {
     NoError,
@@ -92,6 +182,7 @@ fn make_rgl_result<T>(success: T, errors: Vec<Error>) -> RGLResult<T> {
     }
 }
 
+#[allow(clippy::result_unit_err)]
 pub fn get_error() -> Result<Error, ()> {
     let code = unsafe { gl::GetError() };
 
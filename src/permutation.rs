@@ -0,0 +1,220 @@
+use crate::program_builder::StageKind;
+use crate::{FragmentShaderType, ProgramBuildError, ProgramBuilder, ShaderProgram, ShaderType, VertexShaderType};
+use std::collections::{BTreeMap, HashMap};
+
+////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DefineSet {
+    defines: BTreeMap<String, Option<String>>,
+}
+
+impl DefineSet {
+    pub fn new() -> DefineSet {
+        DefineSet::default()
+    }
+
+    pub fn define<N>(mut self, name: N) -> DefineSet where N: Into<String> {
+        self.defines.insert(name.into(), None);
+        self
+    }
+
+    pub fn define_value<N, V>(mut self, name: N, value: V) -> DefineSet where N: Into<String>, V: ToString {
+        self.defines.insert(name.into(), Some(value.to_string()));
+        self
+    }
+
+    pub fn is_defined(&self, name: &str) -> bool {
+        self.defines.contains_key(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.defines
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_deref()))
+    }
+
+    pub fn to_source(&self) -> String {
+        let mut block = String::new();
+
+        for (name, value) in self.iter() {
+            block.push_str("#define ");
+            block.push_str(name);
+
+            if let Some(value) = value {
+                block.push(' ');
+                block.push_str(value);
+            }
+
+            block.push('\n');
+        }
+
+        block
+    }
+}
+
+/// Inserts the `#define` lines of `defines` after the `#version` directive,
+/// which GLSL requires to come first, or at the top if there is none. A
+/// `#line` directive follows them so compile errors keep the line numbers
+/// of `source`.
+pub fn inject_defines(source: &str, defines: &DefineSet) -> String {
+    let block = defines.to_source();
+
+    let mut offset = 0;
+    let mut insert_at = 0;
+    let mut version = None;
+
+    for line in source.split_inclusive('\n') {
+        offset += line.len();
+
+        if let Some(directive) = line.trim_start().strip_prefix("#version") {
+            insert_at = offset;
+            version = Some(directive);
+            break;
+        }
+    }
+
+    let mut result = String::with_capacity(source.len() + block.len() + 1);
+    result.push_str(&source[..insert_at]);
+
+    if insert_at > 0 && !result.ends_with('\n') {
+        result.push('\n');
+    }
+
+    result.push_str(&block);
+    let next_line = source[..insert_at].lines().count() + 1;
+    result.push_str(&format!("#line {}\n", next_line - line_directive_offset(version)));
+    result.push_str(&source[insert_at..]);
+    result
+}
+
+// `#line N` names the next line from GLSL 3.30 and ESSL 3.00 on. Earlier
+// versions, including sources without `#version` (1.10), number it N + 1.
+fn line_directive_offset(version: Option<&str>) -> usize {
+    let mut words = version.unwrap_or("").split_whitespace();
+    let number: u32 = words.next().and_then(|n| n.parse().ok()).unwrap_or(110);
+    let es = words.next() == Some("es");
+
+    if number < 330 && !es {
+        1
+    } else {
+        0
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+
+/// Compiles a set of shader stages once per `DefineSet`, caching the
+/// resulting programs.
+pub struct ShaderPermutations {
+    stages: Vec<(StageKind, String)>,
+    programs: HashMap<DefineSet, ShaderProgram>,
+}
+
+impl ShaderPermutations {
+    pub fn new() -> ShaderPermutations {
+        ShaderPermutations {
+            stages: vec![],
+            programs: HashMap::new(),
+        }
+    }
+
+    pub fn stage<T, S>(mut self, source: S) -> ShaderPermutations where T: ShaderType + 'static, S: Into<String> {
+        self.stages.push((StageKind::of::<T>(), source.into()));
+        self
+    }
+
+    pub fn vertex<S>(self, source: S) -> ShaderPermutations where S: Into<String> {
+        self.stage::<VertexShaderType, S>(source)
+    }
+
+    pub fn fragment<S>(self, source: S) -> ShaderPermutations where S: Into<String> {
+        self.stage::<FragmentShaderType, S>(source)
+    }
+
+    pub fn get(&self, defines: &DefineSet) -> Option<&ShaderProgram> {
+        self.programs.get(defines)
+    }
+
+    pub fn builder(&self, defines: &DefineSet) -> ProgramBuilder {
+        let mut builder = ProgramBuilder::new();
+
+        for (kind, source) in self.stages.iter() {
+            builder.push_stage(*kind, inject_defines(source, defines));
+        }
+
+        builder
+    }
+
+    pub fn get_or_compile(&mut self, defines: &DefineSet) -> Result<&ShaderProgram, ProgramBuildError> {
+        if !self.programs.contains_key(defines) {
+            let program = self.builder(defines).build()?;
+            self.programs.insert(defines.clone(), program);
+        }
+
+        Ok(&self.programs[defines])
+    }
+
+    /// Compiles every variant that is not cached yet and returns the ones
+    /// that failed along with their errors.
+    pub fn compile_all<'a, I>(&mut self, variants: I) -> Vec<(DefineSet, ProgramBuildError)>
+        where I: IntoIterator<Item = &'a DefineSet> {
+        variants
+            .into_iter()
+            .filter_map(|defines| {
+                self.get_or_compile(defines)
+                    .err()
+                    .map(|error| (defines.clone(), error))
+            })
+            .collect()
+    }
+
+    pub fn remove(&mut self, defines: &DefineSet) -> Option<ShaderProgram> {
+        self.programs.remove(defines)
+    }
+
+    pub fn clear(&mut self) {
+        self.programs.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.programs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.programs.is_empty()
+    }
+}
+
+impl Default for ShaderPermutations {
+    fn default() -> ShaderPermutations {
+        ShaderPermutations::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inject_defines_restores_line_numbers() {
+        let defines = DefineSet::new().define("SHADOWS");
+
+        assert_eq!(
+            inject_defines("#version 330 core\nvoid main() {}\n", &defines),
+            "#version 330 core\n#define SHADOWS\n#line 2\nvoid main() {}\n"
+        );
+        assert_eq!(
+            inject_defines("#version 300 es\nvoid main() {}\n", &defines),
+            "#version 300 es\n#define SHADOWS\n#line 2\nvoid main() {}\n"
+        );
+        assert_eq!(
+            inject_defines("// header\n#version 120\nvoid main() {}\n", &defines),
+            "// header\n#version 120\n#define SHADOWS\n#line 2\nvoid main() {}\n"
+        );
+        assert_eq!(
+            inject_defines("// header\nvoid main() {}\n", &defines),
+            "#define SHADOWS\n#line 0\n// header\nvoid main() {}\n"
+        );
+    }
+}
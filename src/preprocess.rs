@@ -0,0 +1,373 @@
+use crate::diagnostics::find_log_location;
+use crate::{CompileShaderError, InfoLog};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+////////////////////////////////////////////////////////////////////
+
+pub trait VirtualFileSystem {
+    fn read_file(&self, path: &str) -> Option<String>;
+}
+
+impl VirtualFileSystem for HashMap<String, String> {
+    fn read_file(&self, path: &str) -> Option<String> {
+        self.get(path).cloned()
+    }
+}
+
+impl VirtualFileSystem for HashMap<&str, &str> {
+    fn read_file(&self, path: &str) -> Option<String> {
+        self.get(path).map(|source| source.to_string())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DirectoryFileSystem {
+    root: PathBuf,
+}
+
+impl DirectoryFileSystem {
+    pub fn new<P>(root: P) -> DirectoryFileSystem where P: Into<PathBuf> {
+        DirectoryFileSystem { root: root.into() }
+    }
+}
+
+impl VirtualFileSystem for DirectoryFileSystem {
+    fn read_file(&self, path: &str) -> Option<String> {
+        fs::read_to_string(self.root.join(path)).ok()
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum IncludeError {
+    NotFound {
+        path: String,
+        included_from: Option<(String, usize)>,
+    },
+    Recursive {
+        path: String,
+        included_from: (String, usize),
+    },
+    MalformedDirective {
+        file: String,
+        line: usize,
+    },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct SourceLine {
+    file: usize,
+    line: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct PreprocessedSource {
+    source: String,
+    files: Vec<String>,
+    lines: Vec<SourceLine>,
+}
+
+impl PreprocessedSource {
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    /// Maps a 1-based line of the expanded source back to the file and line
+    /// it was read from.
+    pub fn resolve_line(&self, line: usize) -> Option<(&str, usize)> {
+        let source_line = self.lines.get(line.checked_sub(1)?)?;
+        Some((&self.files[source_line.file], source_line.line))
+    }
+
+    pub fn map_info_log(&self, log: &str) -> String {
+        log.lines()
+            .map(|log_line| self.map_info_log_line(log_line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Rewrites the raw log with file names and points each diagnostic's
+    /// `source_index` at `files()` and its `line` at the original line.
+    pub fn map_compile_error(&self, error: CompileShaderError) -> CompileShaderError {
+        match error {
+            CompileShaderError::Compile(log) => CompileShaderError::Compile(self.map_log(log)),
+            other => other,
+        }
+    }
+
+    fn map_log(&self, log: InfoLog) -> InfoLog {
+        let mut diagnostics = log.diagnostics;
+
+        for diagnostic in diagnostics.iter_mut() {
+            if diagnostic.source_index != Some(0) {
+                continue;
+            }
+
+            if let Some(source_line) = diagnostic.line.and_then(|l| self.lines.get(l.wrapping_sub(1))) {
+                diagnostic.source_index = Some(source_line.file);
+                diagnostic.line = Some(source_line.line);
+            }
+        }
+
+        InfoLog {
+            raw: self.map_info_log(&log.raw),
+            diagnostics,
+        }
+    }
+
+    fn map_info_log_line(&self, log_line: &str) -> String {
+        if let Some(location) = find_log_location(log_line) {
+            if location.source == 0 {
+                if let Some((file, line)) = self.resolve_line(location.line) {
+                    return format!(
+                        "{}{}:{}{}",
+                        &log_line[..location.start],
+                        file,
+                        line,
+                        &log_line[location.end..]
+                    );
+                }
+            }
+        }
+
+        log_line.to_string()
+    }
+}
+
+pub fn preprocess_includes<V>(vfs: &V, path: &str) -> Result<PreprocessedSource, IncludeError> where V: VirtualFileSystem {
+    let mut preprocessor = Preprocessor {
+        vfs,
+        output: PreprocessedSource {
+            source: String::new(),
+            files: vec![],
+            lines: vec![],
+        },
+        stack: vec![],
+        once: vec![],
+    };
+
+    preprocessor.expand(path, None)?;
+
+    Ok(preprocessor.output)
+}
+
+////////////////////////////////////////////////////////////////////
+
+struct Preprocessor<'a, V> where V: VirtualFileSystem {
+    vfs: &'a V,
+    output: PreprocessedSource,
+    stack: Vec<String>,
+    once: Vec<String>,
+}
+
+impl<'a, V> Preprocessor<'a, V> where V: VirtualFileSystem {
+    fn expand(&mut self, path: &str, included_from: Option<(String, usize)>) -> Result<(), IncludeError> {
+        if self.once.iter().any(|p| p == path) {
+            return Ok(());
+        }
+
+        if let Some(from) = included_from.as_ref() {
+            if self.stack.iter().any(|p| p == path) {
+                return Err(IncludeError::Recursive {
+                    path: path.to_string(),
+                    included_from: from.clone(),
+                });
+            }
+        }
+
+        let source = self.vfs.read_file(path).ok_or_else(|| IncludeError::NotFound {
+            path: path.to_string(),
+            included_from: included_from.clone(),
+        })?;
+
+        let file = self.file_index(path);
+        self.stack.push(path.to_string());
+
+        for (line_index, text) in source.lines().enumerate() {
+            let line = line_index + 1;
+
+            match parse_directive(text) {
+                Some(Directive::Include(include)) => {
+                    let include = include.ok_or_else(|| IncludeError::MalformedDirective {
+                        file: path.to_string(),
+                        line,
+                    })?;
+
+                    let include_path = resolve_include_path(path, include);
+                    self.expand(&include_path, Some((path.to_string(), line)))?;
+                }
+                Some(Directive::PragmaOnce) => {
+                    self.once.push(path.to_string());
+                }
+                None => {
+                    self.output.source.push_str(text);
+                    self.output.source.push('\n');
+                    self.output.lines.push(SourceLine { file, line });
+                }
+            }
+        }
+
+        self.stack.pop();
+
+        Ok(())
+    }
+
+    fn file_index(&mut self, path: &str) -> usize {
+        if let Some(index) = self.output.files.iter().position(|p| p == path) {
+            index
+        } else {
+            self.output.files.push(path.to_string());
+            self.output.files.len() - 1
+        }
+    }
+}
+
+enum Directive<'a> {
+    Include(Option<&'a str>),
+    PragmaOnce,
+}
+
+fn parse_directive(text: &str) -> Option<Directive<'_>> {
+    let directive = text.trim_start().strip_prefix('#')?.trim_start();
+
+    if let Some(rest) = directive.strip_prefix("include") {
+        let rest = rest.trim();
+
+        let quoted = rest.len() > 1 && rest.starts_with('"') && rest.ends_with('"');
+        let bracketed = rest.starts_with('<') && rest.ends_with('>');
+
+        let path = if quoted || bracketed {
+            Some(&rest[1..rest.len() - 1])
+        } else {
+            None
+        };
+
+        Some(Directive::Include(path.filter(|p| !p.is_empty())))
+    } else if directive.split_whitespace().eq(["pragma", "once"].iter().copied()) {
+        Some(Directive::PragmaOnce)
+    } else {
+        None
+    }
+}
+
+// Includes are relative to the including file, '/' separated as in the
+// virtual filesystem, with `..` segments collapsed.
+fn resolve_include_path(including_file: &str, include: &str) -> String {
+    let mut segments: Vec<&str> = if include.starts_with('/') {
+        vec![]
+    } else {
+        let mut dir: Vec<&str> = including_file.split('/').collect();
+        dir.pop();
+        dir
+    };
+
+    for segment in include.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            other => segments.push(other),
+        }
+    }
+
+    segments.retain(|s| !s.is_empty());
+    segments.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_include_paths() {
+        assert_eq!(resolve_include_path("shaders/lit/main.glsl", "light.glsl"), "shaders/lit/light.glsl");
+        assert_eq!(resolve_include_path("shaders/lit/main.glsl", "../common/light.glsl"), "shaders/common/light.glsl");
+        assert_eq!(resolve_include_path("shaders/main.glsl", "./a/../b.glsl"), "shaders/b.glsl");
+        assert_eq!(resolve_include_path("shaders/main.glsl", "/common/light.glsl"), "common/light.glsl");
+        assert_eq!(resolve_include_path("main.glsl", "../../light.glsl"), "light.glsl");
+    }
+
+    #[test]
+    fn include_parent_directory() {
+        let vfs: HashMap<&str, &str> = [
+            ("shaders/lit/main.glsl", "#version 330 core\n#include \"../common/light.glsl\"\nvoid main() {}\n"),
+            ("shaders/common/light.glsl", "uniform vec3 light;\n"),
+        ]
+        .iter()
+        .copied()
+        .collect();
+
+        let source = preprocess_includes(&vfs, "shaders/lit/main.glsl").unwrap();
+
+        assert_eq!(source.source(), "#version 330 core\nuniform vec3 light;\nvoid main() {}\n");
+        assert_eq!(source.resolve_line(2), Some(("shaders/common/light.glsl", 1)));
+        assert_eq!(source.resolve_line(3), Some(("shaders/lit/main.glsl", 3)));
+        assert_eq!(source.map_info_log("0:2(6): error: bad"), "shaders/common/light.glsl:1(6): error: bad");
+    }
+
+    #[test]
+    fn pragma_once() {
+        let vfs: HashMap<&str, &str> = [
+            ("main.glsl", "#include \"common.glsl\"\n#include <common.glsl>\nvoid main() {}\n"),
+            ("common.glsl", "#pragma once\nconst float PI = 3.14159;\n"),
+        ]
+        .iter()
+        .copied()
+        .collect();
+
+        let source = preprocess_includes(&vfs, "main.glsl").unwrap();
+
+        assert_eq!(source.source(), "const float PI = 3.14159;\nvoid main() {}\n");
+    }
+
+    #[test]
+    fn recursive_include() {
+        let vfs: HashMap<&str, &str> = [
+            ("main.glsl", "#include \"a.glsl\"\n"),
+            ("a.glsl", "#include \"b.glsl\"\n"),
+            ("b.glsl", "\n#include \"a.glsl\"\n"),
+        ]
+        .iter()
+        .copied()
+        .collect();
+
+        assert_eq!(
+            preprocess_includes(&vfs, "main.glsl").unwrap_err(),
+            IncludeError::Recursive {
+                path: "a.glsl".to_string(),
+                included_from: ("b.glsl".to_string(), 2),
+            }
+        );
+    }
+
+    #[test]
+    fn include_errors() {
+        let vfs: HashMap<&str, &str> = [("main.glsl", "#include \"missing.glsl\"\n"), ("bad.glsl", "\n#include missing\n")]
+            .iter()
+            .copied()
+            .collect();
+
+        assert_eq!(
+            preprocess_includes(&vfs, "main.glsl").unwrap_err(),
+            IncludeError::NotFound {
+                path: "missing.glsl".to_string(),
+                included_from: Some(("main.glsl".to_string(), 1)),
+            }
+        );
+        assert_eq!(
+            preprocess_includes(&vfs, "bad.glsl").unwrap_err(),
+            IncludeError::MalformedDirective {
+                file: "bad.glsl".to_string(),
+                line: 2,
+            }
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{
+    check_link_status, create_program, get_program_iv, get_rgl_result, get_string, Error,
+    LinkProgramError, RGLResult, ShaderProgram, ShaderProgramObjectParameter, StringName, Unlinked,
+};
+use gl::types::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Debug)]
+pub struct ProgramBinary {
+    pub format: GLenum,
+    pub data: Vec<u8>,
+}
+
+pub fn program_binary_retrievable_hint(program: &ShaderProgram<Unlinked>, retrievable: bool) -> RGLResult<()> {
+    unsafe {
+        gl::ProgramParameteri(
+            program.program_id,
+            gl::PROGRAM_BINARY_RETRIEVABLE_HINT,
+            retrievable as GLint,
+        );
+    }
+
+    get_rgl_result(())
+}
+
+pub fn get_program_binary(program: &ShaderProgram) -> RGLResult<ProgramBinary> {
+    use std::ffi::c_void;
+
+    let len = get_program_iv(program, ShaderProgramObjectParameter::ProgramBinaryLength)?;
+
+    if len <= 0 {
+        return Err(vec![Error::InvalidOperation]);
+    }
+
+    let mut data = vec![0u8; len as usize];
+    let mut written: GLsizei = 0;
+    let mut format: GLenum = 0;
+
+    unsafe {
+        gl::GetProgramBinary(
+            program.program_id,
+            len,
+            &mut written,
+            &mut format,
+            data.as_mut_ptr() as *mut c_void,
+        );
+    }
+
+    data.truncate(written.max(0) as usize);
+
+    get_rgl_result(ProgramBinary { format, data })
+}
+
+pub fn program_binary(program: ShaderProgram<Unlinked>, binary: &ProgramBinary) -> Result<ShaderProgram, LinkProgramError> {
+    use std::ffi::c_void;
+
+    unsafe {
+        gl::ProgramBinary(
+            program.program_id,
+            binary.format,
+            binary.data.as_ptr() as *const c_void,
+            binary.data.len() as GLsizei,
+        );
+    }
+
+    check_link_status(&program)?;
+
+    Ok(program.into_state())
+}
+
+////////////////////////////////////////////////////////////////////
+
+const CACHE_FILE_MAGIC: &[u8; 4] = b"RGLB";
+
+#[derive(Debug)]
+pub enum ProgramCacheError<E> {
+    Build(E),
+    Standard(Vec<Error>),
+}
+
+/// Stores linked program binaries in a directory, keyed by the program's
+/// sources and the vendor, renderer and version strings of the driver.
+#[derive(Clone, Debug)]
+pub struct ProgramBinaryCache {
+    directory: PathBuf,
+    driver_key: String,
+}
+
+impl ProgramBinaryCache {
+    pub fn new<P>(directory: P) -> RGLResult<ProgramBinaryCache> where P: Into<PathBuf> {
+        let driver_key = format!(
+            "{}\n{}\n{}",
+            get_string(StringName::Vendor)?,
+            get_string(StringName::Renderer)?,
+            get_string(StringName::Version)?,
+        );
+
+        Ok(ProgramBinaryCache {
+            directory: directory.into(),
+            driver_key,
+        })
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    pub fn cache_path(&self, sources: &[&str]) -> PathBuf {
+        let mut hash = Fnv1a::new();
+        hash.write(self.driver_key.as_bytes());
+
+        for source in sources {
+            hash.write(&(source.len() as u64).to_le_bytes());
+            hash.write(source.as_bytes());
+        }
+
+        self.directory.join(format!("{:016x}.bin", hash.finish()))
+    }
+
+    /// Loads the program for `sources` from the cache, or creates it with
+    /// `build` when there is no entry or the driver rejects the stored binary.
+    /// `build` is expected to attach the shaders and link the program.
+    ///
+    /// Entries are keyed only by the strings in `sources`. The stage of each
+    /// source and any state `build` sets before linking, like attribute and
+    /// fragment output bindings or transform feedback varyings, are not part
+    /// of the key. Builds that share sources but differ in those have to add
+    /// a string describing the difference to `sources`, or they will load
+    /// each other's binaries.
+    pub fn load_or_build<F, E>(&self, sources: &[&str], build: F) -> Result<ShaderProgram, ProgramCacheError<E>>
+        where F: FnOnce(ShaderProgram<Unlinked>) -> Result<ShaderProgram, E> {
+        let path = self.cache_path(sources);
+
+        if let Some(binary) = read_cache_file(&path) {
+            if let Ok(program) = program_binary(create_program(), &binary) {
+                return Ok(program);
+            }
+
+            let _ = fs::remove_file(&path);
+        }
+
+        let program = create_program();
+
+        program_binary_retrievable_hint(&program, true).map_err(ProgramCacheError::Standard)?;
+        let program = build(program).map_err(ProgramCacheError::Build)?;
+
+        // The program is usable either way, a driver without binary formats
+        // or a failed write only means the next run compiles from source
+        // again.
+        if let Ok(binary) = get_program_binary(&program) {
+            let _ = write_cache_file(&self.directory, &path, &binary);
+        }
+
+        Ok(program)
+    }
+
+    pub fn remove(&self, sources: &[&str]) -> std::io::Result<()> {
+        fs::remove_file(self.cache_path(sources))
+    }
+}
+
+fn read_cache_file(path: &Path) -> Option<ProgramBinary> {
+    let bytes = fs::read(path).ok()?;
+
+    if bytes.len() < 8 || &bytes[..4] != CACHE_FILE_MAGIC {
+        return None;
+    }
+
+    let mut format = [0u8; 4];
+    format.copy_from_slice(&bytes[4..8]);
+
+    Some(ProgramBinary {
+        format: GLenum::from_le_bytes(format),
+        data: bytes[8..].to_vec(),
+    })
+}
+
+fn write_cache_file(directory: &Path, path: &Path, binary: &ProgramBinary) -> std::io::Result<()> {
+    if binary.data.is_empty() {
+        return Ok(());
+    }
+
+    fs::create_dir_all(directory)?;
+
+    let mut bytes = Vec::with_capacity(binary.data.len() + 8);
+    bytes.extend_from_slice(CACHE_FILE_MAGIC);
+    bytes.extend_from_slice(&binary.format.to_le_bytes());
+    bytes.extend_from_slice(&binary.data);
+
+    fs::write(path, bytes)
+}
+
+// Cache keys have to be stable between runs and compiler versions, which
+// rules out std's DefaultHasher.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Fnv1a {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{
+    attach_shader, compile_shader, create_program, create_shader, detach_shader, link_program,
+    shader_source, CompileShaderError, Compiled, Error, FragmentShaderType, LinkProgramError,
+    RGLResult, Shader, ShaderProgram, ShaderType, Unlinked, VertexShaderType,
+};
+use gl::types::*;
+
+////////////////////////////////////////////////////////////////////
+
+pub(crate) trait AttachedStage {
+    fn detach(&self, program: &ShaderProgram) -> RGLResult<()>;
+}
+
+impl<T> AttachedStage for Shader<T, Compiled> where T: ShaderType {
+    fn detach(&self, program: &ShaderProgram) -> RGLResult<()> {
+        detach_shader(program, self)
+    }
+}
+
+type CompileStageFn = fn(&ShaderProgram<Unlinked>, &str) -> Result<Box<dyn AttachedStage>, CompileShaderError>;
+
+fn compile_stage<T>(program: &ShaderProgram<Unlinked>, source: &str) -> Result<Box<dyn AttachedStage>, CompileShaderError>
+    where T: ShaderType + 'static {
+    let shader = create_shader::<T>();
+
+    shader_source(&shader, source).map_err(CompileShaderError::Standard)?;
+    let shader = compile_shader(shader)?;
+    attach_shader(program, &shader).map_err(CompileShaderError::Standard)?;
+
+    Ok(Box::new(shader))
+}
+
+// The shader type of a stage, erased so stages of different types can be
+// kept in one list.
+#[derive(Copy, Clone)]
+pub(crate) struct StageKind {
+    stage: GLenum,
+    compile: CompileStageFn,
+}
+
+impl StageKind {
+    pub(crate) fn of<T>() -> StageKind where T: ShaderType + 'static {
+        StageKind {
+            stage: T::to_gl_code(),
+            compile: compile_stage::<T>,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Debug)]
+pub struct StageCompileError {
+    pub index: usize,
+    pub stage: GLenum,
+    pub error: CompileShaderError,
+}
+
+#[derive(Clone, Debug)]
+pub enum ProgramBuildError {
+    Compile(Vec<StageCompileError>),
+    Link(LinkProgramError),
+    Standard(Vec<Error>),
+}
+
+pub struct ProgramBuilder {
+    stages: Vec<(StageKind, String)>,
+}
+
+impl ProgramBuilder {
+    pub fn new() -> ProgramBuilder {
+        ProgramBuilder { stages: vec![] }
+    }
+
+    pub fn stage<T, S>(mut self, source: S) -> ProgramBuilder where T: ShaderType + 'static, S: Into<String> {
+        self.push_stage(StageKind::of::<T>(), source.into());
+        self
+    }
+
+    pub fn vertex<S>(self, source: S) -> ProgramBuilder where S: Into<String> {
+        self.stage::<VertexShaderType, S>(source)
+    }
+
+    pub fn fragment<S>(self, source: S) -> ProgramBuilder where S: Into<String> {
+        self.stage::<FragmentShaderType, S>(source)
+    }
+
+    pub(crate) fn push_stage(&mut self, kind: StageKind, source: String) {
+        self.stages.push((kind, source));
+    }
+
+    pub fn sources(&self) -> Vec<&str> {
+        self.stages.iter().map(|(_, source)| source.as_str()).collect()
+    }
+
+    pub fn build(&self) -> Result<ShaderProgram, ProgramBuildError> {
+        self.build_into(create_program())
+    }
+
+    /// Compiles every stage, attaches them to `program` and links it. All
+    /// stages are compiled even if one fails, so every compile error is
+    /// reported. The shaders are detached and deleted once linking is done.
+    pub fn build_into(&self, mut program: ShaderProgram<Unlinked>) -> Result<ShaderProgram, ProgramBuildError> {
+        let mut attached = Vec::with_capacity(self.stages.len());
+        let mut errors = vec![];
+
+        for (index, (kind, source)) in self.stages.iter().enumerate() {
+            match (kind.compile)(&program, source) {
+                Ok(shader) => attached.push(shader),
+                Err(error) => errors.push(StageCompileError {
+                    index,
+                    stage: kind.stage,
+                    error,
+                }),
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(ProgramBuildError::Compile(errors));
+        }
+
+        // The shaders are detached below, so hand their sources over for
+        // uniform lookups to scan if they need to.
+        program.set_uniform_sources(self.stages.iter().map(|(_, source)| source.clone()).collect());
+
+        let program = link_program(program).map_err(ProgramBuildError::Link)?;
+
+        for shader in attached.iter() {
+            shader.detach(&program).map_err(ProgramBuildError::Standard)?;
+        }
+
+        Ok(program)
+    }
+}
+
+impl Default for ProgramBuilder {
+    fn default() -> ProgramBuilder {
+        ProgramBuilder::new()
+    }
+}
@@ -0,0 +1,458 @@
+use crate::{get_rgl_result, Error, GlslType, RGLResult, ShaderProgram};
+use gl::types::*;
+use std::ffi::CString;
+
+////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProgramInterface {
+    Uniform,
+    UniformBlock,
+    ProgramInput,
+    ProgramOutput,
+    BufferVariable,
+    ShaderStorageBlock,
+    AtomicCounterBuffer,
+    TransformFeedbackVarying,
+    TransformFeedbackBuffer,
+}
+
+impl ProgramInterface {
+    fn to_gl_code(self) -> GLenum {
+        match self {
+            ProgramInterface::Uniform => gl::UNIFORM,
+            ProgramInterface::UniformBlock => gl::UNIFORM_BLOCK,
+            ProgramInterface::ProgramInput => gl::PROGRAM_INPUT,
+            ProgramInterface::ProgramOutput => gl::PROGRAM_OUTPUT,
+            ProgramInterface::BufferVariable => gl::BUFFER_VARIABLE,
+            ProgramInterface::ShaderStorageBlock => gl::SHADER_STORAGE_BLOCK,
+            ProgramInterface::AtomicCounterBuffer => gl::ATOMIC_COUNTER_BUFFER,
+            ProgramInterface::TransformFeedbackVarying => gl::TRANSFORM_FEEDBACK_VARYING,
+            ProgramInterface::TransformFeedbackBuffer => gl::TRANSFORM_FEEDBACK_BUFFER,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum ProgramInterfaceParameter {
+    ActiveResources,
+    MaxNameLength,
+    MaxNumActiveVariables,
+}
+
+impl ProgramInterfaceParameter {
+    fn to_gl_code(self) -> GLenum {
+        match self {
+            ProgramInterfaceParameter::ActiveResources => gl::ACTIVE_RESOURCES,
+            ProgramInterfaceParameter::MaxNameLength => gl::MAX_NAME_LENGTH,
+            ProgramInterfaceParameter::MaxNumActiveVariables => gl::MAX_NUM_ACTIVE_VARIABLES,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum ProgramResourceProperty {
+    NameLength,
+    Type,
+    ArraySize,
+    Offset,
+    BlockIndex,
+    ArrayStride,
+    MatrixStride,
+    IsRowMajor,
+    AtomicCounterBufferIndex,
+    BufferBinding,
+    BufferDataSize,
+    NumActiveVariables,
+    TopLevelArraySize,
+    TopLevelArrayStride,
+    Location,
+    LocationIndex,
+    TransformFeedbackBufferIndex,
+    TransformFeedbackBufferStride,
+}
+
+impl ProgramResourceProperty {
+    fn to_gl_code(self) -> GLenum {
+        match self {
+            ProgramResourceProperty::NameLength => gl::NAME_LENGTH,
+            ProgramResourceProperty::Type => gl::TYPE,
+            ProgramResourceProperty::ArraySize => gl::ARRAY_SIZE,
+            ProgramResourceProperty::Offset => gl::OFFSET,
+            ProgramResourceProperty::BlockIndex => gl::BLOCK_INDEX,
+            ProgramResourceProperty::ArrayStride => gl::ARRAY_STRIDE,
+            ProgramResourceProperty::MatrixStride => gl::MATRIX_STRIDE,
+            ProgramResourceProperty::IsRowMajor => gl::IS_ROW_MAJOR,
+            ProgramResourceProperty::AtomicCounterBufferIndex => gl::ATOMIC_COUNTER_BUFFER_INDEX,
+            ProgramResourceProperty::BufferBinding => gl::BUFFER_BINDING,
+            ProgramResourceProperty::BufferDataSize => gl::BUFFER_DATA_SIZE,
+            ProgramResourceProperty::NumActiveVariables => gl::NUM_ACTIVE_VARIABLES,
+            ProgramResourceProperty::TopLevelArraySize => gl::TOP_LEVEL_ARRAY_SIZE,
+            ProgramResourceProperty::TopLevelArrayStride => gl::TOP_LEVEL_ARRAY_STRIDE,
+            ProgramResourceProperty::Location => gl::LOCATION,
+            ProgramResourceProperty::LocationIndex => gl::LOCATION_INDEX,
+            ProgramResourceProperty::TransformFeedbackBufferIndex => gl::TRANSFORM_FEEDBACK_BUFFER_INDEX,
+            ProgramResourceProperty::TransformFeedbackBufferStride => gl::TRANSFORM_FEEDBACK_BUFFER_STRIDE,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+
+pub fn get_program_interface_iv(
+    program: &ShaderProgram,
+    interface: ProgramInterface,
+    pname: ProgramInterfaceParameter,
+) -> RGLResult<GLint> {
+    let mut result: GLint = 0;
+
+    unsafe {
+        gl::GetProgramInterfaceiv(
+            program.program_id,
+            interface.to_gl_code(),
+            pname.to_gl_code(),
+            &mut result,
+        );
+    }
+
+    get_rgl_result(result)
+}
+
+pub fn get_program_resource_iv(
+    program: &ShaderProgram,
+    interface: ProgramInterface,
+    index: GLuint,
+    props: &[ProgramResourceProperty],
+) -> RGLResult<Vec<GLint>> {
+    let gl_props: Vec<GLenum> = props.iter().map(|p| p.to_gl_code()).collect();
+    let mut values: Vec<GLint> = vec![0; props.len()];
+
+    unsafe {
+        gl::GetProgramResourceiv(
+            program.program_id,
+            interface.to_gl_code(),
+            index,
+            gl_props.len() as GLsizei,
+            gl_props.as_ptr(),
+            values.len() as GLsizei,
+            std::ptr::null_mut(),
+            values.as_mut_ptr(),
+        );
+    }
+
+    get_rgl_result(values)
+}
+
+fn get_active_variable_indices(
+    program: &ShaderProgram,
+    interface: ProgramInterface,
+    index: GLuint,
+    count: GLint,
+) -> RGLResult<Vec<GLuint>> {
+    let prop = gl::ACTIVE_VARIABLES;
+    let mut values: Vec<GLint> = vec![0; count.max(0) as usize];
+
+    unsafe {
+        gl::GetProgramResourceiv(
+            program.program_id,
+            interface.to_gl_code(),
+            index,
+            1,
+            &prop,
+            values.len() as GLsizei,
+            std::ptr::null_mut(),
+            values.as_mut_ptr(),
+        );
+    }
+
+    get_rgl_result(values.iter().map(|v| *v as GLuint).collect())
+}
+
+/// Returns `None` when `name` is not an active resource of the interface.
+pub fn get_program_resource_index(
+    program: &ShaderProgram,
+    interface: ProgramInterface,
+    name: &str,
+) -> RGLResult<Option<GLuint>> {
+    let c_name = CString::new(name).map_err(|_| vec![Error::InvalidValue])?;
+
+    let index = unsafe {
+        gl::GetProgramResourceIndex(program.program_id, interface.to_gl_code(), c_name.as_ptr())
+    };
+
+    get_rgl_result(if index == gl::INVALID_INDEX { None } else { Some(index) })
+}
+
+pub fn get_program_resource_location(
+    program: &ShaderProgram,
+    interface: ProgramInterface,
+    name: &str,
+) -> RGLResult<GLint> {
+    let c_name = CString::new(name).map_err(|_| vec![Error::InvalidValue])?;
+
+    let location = unsafe {
+        gl::GetProgramResourceLocation(program.program_id, interface.to_gl_code(), c_name.as_ptr())
+    };
+
+    get_rgl_result(location)
+}
+
+pub fn get_program_resource_name(
+    program: &ShaderProgram,
+    interface: ProgramInterface,
+    index: GLuint,
+) -> RGLResult<String> {
+    let len = get_program_resource_iv(program, interface, index, &[ProgramResourceProperty::NameLength])?[0];
+
+    let mut buffer: Vec<u8> = vec![0; len.max(1) as usize];
+    let mut written: GLsizei = 0;
+
+    unsafe {
+        gl::GetProgramResourceName(
+            program.program_id,
+            interface.to_gl_code(),
+            index,
+            buffer.len() as GLsizei,
+            &mut written,
+            buffer.as_mut_ptr() as *mut GLchar,
+        );
+    }
+
+    buffer.truncate(written.max(0) as usize);
+
+    get_rgl_result(String::from_utf8_lossy(&buffer).into_owned())
+}
+
+////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockMember {
+    pub name: String,
+    pub glsl_type: GlslType,
+    pub offset: GLint,
+    pub array_size: GLint,
+    pub array_stride: GLint,
+    pub matrix_stride: GLint,
+    pub row_major: bool,
+    /// Only reported for shader storage block members.
+    pub top_level_array_size: Option<GLint>,
+    pub top_level_array_stride: Option<GLint>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BufferBlock {
+    pub name: String,
+    pub index: GLuint,
+    pub binding: GLuint,
+    pub data_size: GLint,
+    pub members: Vec<BlockMember>,
+}
+
+impl BufferBlock {
+    /// Finds a member by its name, with or without the block name prefix
+    /// GL reports for blocks that have an instance name.
+    pub fn member(&self, name: &str) -> Option<&BlockMember> {
+        let prefixed = format!("{}.{}", self.name, name);
+
+        self.members
+            .iter()
+            .find(|m| m.name == name)
+            .or_else(|| self.members.iter().find(|m| m.name == prefixed))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProgramOutput {
+    pub name: String,
+    pub glsl_type: GlslType,
+    pub array_size: GLint,
+    pub location: GLint,
+    pub location_index: GLint,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransformFeedbackVarying {
+    pub name: String,
+    pub glsl_type: GlslType,
+    pub array_size: GLint,
+    pub offset: GLint,
+    pub buffer_index: GLint,
+}
+
+fn get_active_resource_count(program: &ShaderProgram, interface: ProgramInterface) -> RGLResult<GLuint> {
+    let count = get_program_interface_iv(program, interface, ProgramInterfaceParameter::ActiveResources)?;
+    Ok(count.max(0) as GLuint)
+}
+
+fn get_buffer_blocks(
+    program: &ShaderProgram,
+    block_interface: ProgramInterface,
+    member_interface: ProgramInterface,
+) -> RGLResult<Vec<BufferBlock>> {
+    use ProgramResourceProperty::*;
+
+    let mut blocks = vec![];
+
+    for index in 0..get_active_resource_count(program, block_interface)? {
+        let values = get_program_resource_iv(
+            program,
+            block_interface,
+            index,
+            &[BufferBinding, BufferDataSize, NumActiveVariables],
+        )?;
+
+        let member_indices = get_active_variable_indices(program, block_interface, index, values[2])?;
+        let mut members = Vec::with_capacity(member_indices.len());
+
+        for member_index in member_indices {
+            let storage = member_interface == ProgramInterface::BufferVariable;
+
+            let props: &[ProgramResourceProperty] = if storage {
+                &[Type, Offset, ArraySize, ArrayStride, MatrixStride, IsRowMajor, TopLevelArraySize, TopLevelArrayStride]
+            } else {
+                &[Type, Offset, ArraySize, ArrayStride, MatrixStride, IsRowMajor]
+            };
+
+            let member = get_program_resource_iv(program, member_interface, member_index, props)?;
+
+            members.push(BlockMember {
+                name: get_program_resource_name(program, member_interface, member_index)?,
+                glsl_type: GlslType::from_gl_code(member[0] as GLenum),
+                offset: member[1],
+                array_size: member[2],
+                array_stride: member[3],
+                matrix_stride: member[4],
+                row_major: member[5] != 0,
+                top_level_array_size: member.get(6).copied(),
+                top_level_array_stride: member.get(7).copied(),
+            });
+        }
+
+        members.sort_by_key(|m| m.offset);
+
+        blocks.push(BufferBlock {
+            name: get_program_resource_name(program, block_interface, index)?,
+            index,
+            binding: values[0] as GLuint,
+            data_size: values[1],
+            members,
+        });
+    }
+
+    Ok(blocks)
+}
+
+pub fn get_uniform_blocks(program: &ShaderProgram) -> RGLResult<Vec<BufferBlock>> {
+    get_buffer_blocks(program, ProgramInterface::UniformBlock, ProgramInterface::Uniform)
+}
+
+pub fn get_shader_storage_blocks(program: &ShaderProgram) -> RGLResult<Vec<BufferBlock>> {
+    get_buffer_blocks(program, ProgramInterface::ShaderStorageBlock, ProgramInterface::BufferVariable)
+}
+
+pub fn get_program_outputs(program: &ShaderProgram) -> RGLResult<Vec<ProgramOutput>> {
+    use ProgramResourceProperty::*;
+
+    (0..get_active_resource_count(program, ProgramInterface::ProgramOutput)?)
+        .map(|index| {
+            let values = get_program_resource_iv(
+                program,
+                ProgramInterface::ProgramOutput,
+                index,
+                &[Type, ArraySize, Location, LocationIndex],
+            )?;
+
+            Ok(ProgramOutput {
+                name: get_program_resource_name(program, ProgramInterface::ProgramOutput, index)?,
+                glsl_type: GlslType::from_gl_code(values[0] as GLenum),
+                array_size: values[1],
+                location: values[2],
+                location_index: values[3],
+            })
+        })
+        .collect()
+}
+
+pub fn get_transform_feedback_varyings(program: &ShaderProgram) -> RGLResult<Vec<TransformFeedbackVarying>> {
+    use ProgramResourceProperty::*;
+
+    (0..get_active_resource_count(program, ProgramInterface::TransformFeedbackVarying)?)
+        .map(|index| {
+            let values = get_program_resource_iv(
+                program,
+                ProgramInterface::TransformFeedbackVarying,
+                index,
+                &[Type, ArraySize, Offset, TransformFeedbackBufferIndex],
+            )?;
+
+            Ok(TransformFeedbackVarying {
+                name: get_program_resource_name(program, ProgramInterface::TransformFeedbackVarying, index)?,
+                glsl_type: GlslType::from_gl_code(values[0] as GLenum),
+                array_size: values[1],
+                offset: values[2],
+                buffer_index: values[3],
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_member(name: &str, offset: GLint) -> BlockMember {
+        BlockMember {
+            name: name.to_string(),
+            glsl_type: GlslType::Float,
+            offset,
+            array_size: 1,
+            array_stride: 0,
+            matrix_stride: 0,
+            row_major: false,
+            top_level_array_size: None,
+            top_level_array_stride: None,
+        }
+    }
+
+    fn buffer_block(name: &str, members: Vec<BlockMember>) -> BufferBlock {
+        BufferBlock {
+            name: name.to_string(),
+            index: 0,
+            binding: 0,
+            data_size: 32,
+            members,
+        }
+    }
+
+    #[test]
+    fn member_prefers_top_level_names() {
+        // uniform Block { Light light; float intensity; }
+        let block = buffer_block(
+            "Block",
+            vec![
+                block_member("light.position", 0),
+                block_member("light.intensity", 12),
+                block_member("intensity", 16),
+            ],
+        );
+
+        assert_eq!(block.member("intensity").map(|m| m.offset), Some(16));
+        assert_eq!(block.member("light.intensity").map(|m| m.offset), Some(12));
+        assert_eq!(block.member("position"), None);
+    }
+
+    #[test]
+    fn member_with_instance_name() {
+        // uniform Block { Light light; float intensity; } block;
+        let block = buffer_block(
+            "Block",
+            vec![
+                block_member("Block.light.intensity", 12),
+                block_member("Block.intensity", 16),
+            ],
+        );
+
+        assert_eq!(block.member("intensity").map(|m| m.offset), Some(16));
+        assert_eq!(block.member("light.intensity").map(|m| m.offset), Some(12));
+    }
+}
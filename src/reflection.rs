@@ -0,0 +1,263 @@
+use crate::{get_program_iv, get_rgl_result, RGLResult, ShaderProgram, ShaderProgramObjectParameter};
+use gl::types::*;
+use std::ffi::CString;
+
+////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GlslType {
+    Float,
+    FloatVec2,
+    FloatVec3,
+    FloatVec4,
+    Double,
+    DoubleVec2,
+    DoubleVec3,
+    DoubleVec4,
+    Int,
+    IntVec2,
+    IntVec3,
+    IntVec4,
+    UnsignedInt,
+    UnsignedIntVec2,
+    UnsignedIntVec3,
+    UnsignedIntVec4,
+    Bool,
+    BoolVec2,
+    BoolVec3,
+    BoolVec4,
+    FloatMat2,
+    FloatMat3,
+    FloatMat4,
+    FloatMat2x3,
+    FloatMat2x4,
+    FloatMat3x2,
+    FloatMat3x4,
+    FloatMat4x2,
+    FloatMat4x3,
+    DoubleMat2,
+    DoubleMat3,
+    DoubleMat4,
+    DoubleMat2x3,
+    DoubleMat2x4,
+    DoubleMat3x2,
+    DoubleMat3x4,
+    DoubleMat4x2,
+    DoubleMat4x3,
+    Sampler1D,
+    Sampler2D,
+    Sampler3D,
+    SamplerCube,
+    Sampler2DShadow,
+    Sampler2DArray,
+    Sampler2DArrayShadow,
+    SamplerCubeShadow,
+    Sampler2DMultisample,
+    SamplerBuffer,
+    IntSampler2D,
+    UnsignedIntSampler2D,
+    Image2D,
+    AtomicUnsignedInt,
+    Other(GLenum),
+}
+
+impl GlslType {
+    pub fn from_gl_code(code: GLenum) -> GlslType {
+        match code {
+            gl::FLOAT => GlslType::Float,
+            gl::FLOAT_VEC2 => GlslType::FloatVec2,
+            gl::FLOAT_VEC3 => GlslType::FloatVec3,
+            gl::FLOAT_VEC4 => GlslType::FloatVec4,
+            gl::DOUBLE => GlslType::Double,
+            gl::DOUBLE_VEC2 => GlslType::DoubleVec2,
+            gl::DOUBLE_VEC3 => GlslType::DoubleVec3,
+            gl::DOUBLE_VEC4 => GlslType::DoubleVec4,
+            gl::INT => GlslType::Int,
+            gl::INT_VEC2 => GlslType::IntVec2,
+            gl::INT_VEC3 => GlslType::IntVec3,
+            gl::INT_VEC4 => GlslType::IntVec4,
+            gl::UNSIGNED_INT => GlslType::UnsignedInt,
+            gl::UNSIGNED_INT_VEC2 => GlslType::UnsignedIntVec2,
+            gl::UNSIGNED_INT_VEC3 => GlslType::UnsignedIntVec3,
+            gl::UNSIGNED_INT_VEC4 => GlslType::UnsignedIntVec4,
+            gl::BOOL => GlslType::Bool,
+            gl::BOOL_VEC2 => GlslType::BoolVec2,
+            gl::BOOL_VEC3 => GlslType::BoolVec3,
+            gl::BOOL_VEC4 => GlslType::BoolVec4,
+            gl::FLOAT_MAT2 => GlslType::FloatMat2,
+            gl::FLOAT_MAT3 => GlslType::FloatMat3,
+            gl::FLOAT_MAT4 => GlslType::FloatMat4,
+            gl::FLOAT_MAT2x3 => GlslType::FloatMat2x3,
+            gl::FLOAT_MAT2x4 => GlslType::FloatMat2x4,
+            gl::FLOAT_MAT3x2 => GlslType::FloatMat3x2,
+            gl::FLOAT_MAT3x4 => GlslType::FloatMat3x4,
+            gl::FLOAT_MAT4x2 => GlslType::FloatMat4x2,
+            gl::FLOAT_MAT4x3 => GlslType::FloatMat4x3,
+            gl::DOUBLE_MAT2 => GlslType::DoubleMat2,
+            gl::DOUBLE_MAT3 => GlslType::DoubleMat3,
+            gl::DOUBLE_MAT4 => GlslType::DoubleMat4,
+            gl::DOUBLE_MAT2x3 => GlslType::DoubleMat2x3,
+            gl::DOUBLE_MAT2x4 => GlslType::DoubleMat2x4,
+            gl::DOUBLE_MAT3x2 => GlslType::DoubleMat3x2,
+            gl::DOUBLE_MAT3x4 => GlslType::DoubleMat3x4,
+            gl::DOUBLE_MAT4x2 => GlslType::DoubleMat4x2,
+            gl::DOUBLE_MAT4x3 => GlslType::DoubleMat4x3,
+            gl::SAMPLER_1D => GlslType::Sampler1D,
+            gl::SAMPLER_2D => GlslType::Sampler2D,
+            gl::SAMPLER_3D => GlslType::Sampler3D,
+            gl::SAMPLER_CUBE => GlslType::SamplerCube,
+            gl::SAMPLER_2D_SHADOW => GlslType::Sampler2DShadow,
+            gl::SAMPLER_2D_ARRAY => GlslType::Sampler2DArray,
+            gl::SAMPLER_2D_ARRAY_SHADOW => GlslType::Sampler2DArrayShadow,
+            gl::SAMPLER_CUBE_SHADOW => GlslType::SamplerCubeShadow,
+            gl::SAMPLER_2D_MULTISAMPLE => GlslType::Sampler2DMultisample,
+            gl::SAMPLER_BUFFER => GlslType::SamplerBuffer,
+            gl::INT_SAMPLER_2D => GlslType::IntSampler2D,
+            gl::UNSIGNED_INT_SAMPLER_2D => GlslType::UnsignedIntSampler2D,
+            gl::IMAGE_2D => GlslType::Image2D,
+            gl::UNSIGNED_INT_ATOMIC_COUNTER => GlslType::AtomicUnsignedInt,
+            other => GlslType::Other(other),
+        }
+    }
+
+    /// Whether this is a sampler or image type, including the ones without
+    /// their own variant. Their uniforms hold a texture unit or image unit.
+    pub fn is_sampler_or_image(self) -> bool {
+        match self {
+            GlslType::Sampler1D
+            | GlslType::Sampler2D
+            | GlslType::Sampler3D
+            | GlslType::SamplerCube
+            | GlslType::Sampler2DShadow
+            | GlslType::Sampler2DArray
+            | GlslType::Sampler2DArrayShadow
+            | GlslType::SamplerCubeShadow
+            | GlslType::Sampler2DMultisample
+            | GlslType::SamplerBuffer
+            | GlslType::IntSampler2D
+            | GlslType::UnsignedIntSampler2D
+            | GlslType::Image2D => true,
+            GlslType::Other(code) => matches!(
+                code,
+                gl::SAMPLER_1D..=gl::SAMPLER_2D_RECT_SHADOW
+                    | gl::SAMPLER_1D_ARRAY..=gl::SAMPLER_CUBE_SHADOW
+                    | gl::INT_SAMPLER_1D..=gl::UNSIGNED_INT_SAMPLER_BUFFER
+                    | gl::SAMPLER_CUBE_MAP_ARRAY..=gl::UNSIGNED_INT_SAMPLER_CUBE_MAP_ARRAY
+                    | gl::IMAGE_1D..=gl::UNSIGNED_INT_IMAGE_2D_MULTISAMPLE_ARRAY
+                    | gl::SAMPLER_2D_MULTISAMPLE..=gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE_ARRAY
+            ),
+            _ => false,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActiveVariable {
+    pub name: String,
+    pub glsl_type: GlslType,
+    pub size: GLint,
+    pub location: GLint,
+}
+
+type GetActiveFn = unsafe fn(GLuint, GLuint, GLsizei, *mut GLsizei, *mut GLint, *mut GLenum, *mut GLchar);
+type GetLocationFn = unsafe fn(GLuint, *const GLchar) -> GLint;
+
+fn get_active_variable(
+    program: &ShaderProgram,
+    index: GLuint,
+    max_length: GLint,
+    get_active: GetActiveFn,
+    get_location: GetLocationFn,
+) -> RGLResult<ActiveVariable> {
+    let mut buffer: Vec<u8> = vec![0; max_length.max(1) as usize];
+    let mut written: GLsizei = 0;
+    let mut size: GLint = 0;
+    let mut gl_type: GLenum = 0;
+
+    unsafe {
+        get_active(
+            program.program_id,
+            index,
+            buffer.len() as GLsizei,
+            &mut written,
+            &mut size,
+            &mut gl_type,
+            buffer.as_mut_ptr() as *mut GLchar,
+        );
+    }
+
+    buffer.truncate(written.max(0) as usize);
+
+    let location = match CString::new(buffer.clone()) {
+        Ok(c_name) => unsafe { get_location(program.program_id, c_name.as_ptr()) },
+        Err(_) => -1,
+    };
+
+    get_rgl_result(ActiveVariable {
+        name: String::from_utf8_lossy(&buffer).into_owned(),
+        glsl_type: GlslType::from_gl_code(gl_type),
+        size,
+        location,
+    })
+}
+
+pub fn get_active_attrib(program: &ShaderProgram, index: GLuint) -> RGLResult<ActiveVariable> {
+    let max_length = get_program_iv(program, ShaderProgramObjectParameter::ActiveAttributeMaxLength)?;
+
+    get_active_variable(program, index, max_length, gl::GetActiveAttrib, gl::GetAttribLocation)
+}
+
+pub fn get_active_uniform(program: &ShaderProgram, index: GLuint) -> RGLResult<ActiveVariable> {
+    let max_length = get_program_iv(program, ShaderProgramObjectParameter::ActiveUniformMaxLength)?;
+
+    get_active_variable(program, index, max_length, gl::GetActiveUniform, gl::GetUniformLocation)
+}
+
+pub fn get_active_attributes(program: &ShaderProgram) -> RGLResult<Vec<ActiveVariable>> {
+    let count = get_program_iv(program, ShaderProgramObjectParameter::ActiveAttributes)?;
+    let max_length = get_program_iv(program, ShaderProgramObjectParameter::ActiveAttributeMaxLength)?;
+
+    (0..count.max(0) as GLuint)
+        .map(|index| {
+            get_active_variable(program, index, max_length, gl::GetActiveAttrib, gl::GetAttribLocation)
+        })
+        .collect()
+}
+
+pub fn get_active_uniforms(program: &ShaderProgram) -> RGLResult<Vec<ActiveVariable>> {
+    let count = get_program_iv(program, ShaderProgramObjectParameter::ActiveUniforms)?;
+    let max_length = get_program_iv(program, ShaderProgramObjectParameter::ActiveUniformMaxLength)?;
+
+    (0..count.max(0) as GLuint)
+        .map(|index| {
+            get_active_variable(program, index, max_length, gl::GetActiveUniform, gl::GetUniformLocation)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sampler_and_image_types() {
+        for code in [
+            gl::INT_SAMPLER_3D,
+            gl::UNSIGNED_INT_SAMPLER_CUBE,
+            gl::SAMPLER_1D_SHADOW,
+            gl::SAMPLER_2D_RECT,
+            gl::SAMPLER_CUBE_MAP_ARRAY_SHADOW,
+            gl::IMAGE_3D,
+            gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE_ARRAY,
+        ] {
+            assert!(GlslType::from_gl_code(code).is_sampler_or_image(), "{:#x}", code);
+        }
+
+        // UNSIGNED_INT_VEC* sit between the sampler enums.
+        for code in [gl::UNSIGNED_INT_VEC2, gl::FLOAT_VEC2, gl::UNSIGNED_INT_ATOMIC_COUNTER] {
+            assert!(!GlslType::from_gl_code(code).is_sampler_or_image(), "{:#x}", code);
+        }
+    }
+}
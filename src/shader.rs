@@ -1,23 +1,60 @@
 use crate::get_rgl_result;
+use crate::DeclaredUniform;
+use crate::InfoLog;
 use crate::Error;
 use crate::RGLResult;
 use gl::types::*;
+use std::cell::OnceCell;
 use std::marker::PhantomData;
 
 pub struct VertexShaderType;
+pub struct TessControlShaderType;
+pub struct TessEvaluationShaderType;
+pub struct GeometryShaderType;
 pub struct FragmentShaderType;
+pub struct ComputeShaderType;
+
+pub struct Uncompiled;
+pub struct Compiled;
+
+pub struct Unlinked;
+pub struct Linked;
 
 mod private {
 
     use super::{
         VertexShaderType,
+        TessControlShaderType,
+        TessEvaluationShaderType,
+        GeometryShaderType,
         FragmentShaderType,
+        ComputeShaderType,
+
+        Uncompiled,
+        Compiled,
+
+        Unlinked,
+        Linked,
     };
 
     pub trait PrivShaderType {}
 
     impl PrivShaderType for VertexShaderType {}
+    impl PrivShaderType for TessControlShaderType {}
+    impl PrivShaderType for TessEvaluationShaderType {}
+    impl PrivShaderType for GeometryShaderType {}
     impl PrivShaderType for FragmentShaderType {}
+    impl PrivShaderType for ComputeShaderType {}
+
+    pub trait PrivShaderState {}
+
+    impl PrivShaderState for Uncompiled {}
+    impl PrivShaderState for Compiled {}
+
+    pub trait PrivProgramState {}
+
+    impl PrivProgramState for Unlinked {}
+    impl PrivProgramState for Linked {}
 }
 
 pub trait ShaderType : private::PrivShaderType {
@@ -30,19 +67,67 @@ impl ShaderType for VertexShaderType {
     }
 }
 
+impl ShaderType for TessControlShaderType {
+    fn to_gl_code() -> GLenum {
+        gl::TESS_CONTROL_SHADER
+    }
+}
+
+impl ShaderType for TessEvaluationShaderType {
+    fn to_gl_code() -> GLenum {
+        gl::TESS_EVALUATION_SHADER
+    }
+}
+
+impl ShaderType for GeometryShaderType {
+    fn to_gl_code() -> GLenum {
+        gl::GEOMETRY_SHADER
+    }
+}
+
 impl ShaderType for FragmentShaderType {
     fn to_gl_code() -> GLenum {
         gl::FRAGMENT_SHADER
     }
 }
 
+impl ShaderType for ComputeShaderType {
+    fn to_gl_code() -> GLenum {
+        gl::COMPUTE_SHADER
+    }
+}
+
+pub trait ShaderState : private::PrivShaderState {}
+
+impl ShaderState for Uncompiled {}
+impl ShaderState for Compiled {}
+
+pub trait ProgramState : private::PrivProgramState {}
+
+impl ProgramState for Unlinked {}
+impl ProgramState for Linked {}
+
 #[derive(Debug)]
-pub struct Shader<T> where T: ShaderType {
-    shader_id: GLuint,
+pub struct Shader<T, S = Compiled> where T: ShaderType, S: ShaderState {
+    pub(crate) shader_id: GLuint,
     type_marker: PhantomData<T>,
+    state_marker: PhantomData<S>,
 }
 
-impl<T> Drop for Shader<T> where T: ShaderType {
+impl<T, S> Shader<T, S> where T: ShaderType, S: ShaderState {
+    pub(crate) fn into_state<N>(self) -> Shader<T, N> where N: ShaderState {
+        let shader_id = self.shader_id;
+        std::mem::forget(self);
+
+        Shader {
+            shader_id,
+            type_marker: PhantomData,
+            state_marker: PhantomData,
+        }
+    }
+}
+
+impl<T, S> Drop for Shader<T, S> where T: ShaderType, S: ShaderState {
     fn drop(&mut self) {
         unsafe {
             gl::DeleteShader(self.shader_id);
@@ -71,12 +156,83 @@ impl ShaderObjectParameter {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+enum LocationBinding {
+    Attrib { name: String, location: GLuint },
+    FragData { name: String, color_number: GLuint, index: GLuint },
+}
+
+impl LocationBinding {
+    fn replaces(&self, other: &LocationBinding) -> bool {
+        match (self, other) {
+            (LocationBinding::Attrib { name, .. }, LocationBinding::Attrib { name: other, .. }) => name == other,
+            (LocationBinding::FragData { name, .. }, LocationBinding::FragData { name: other, .. }) => name == other,
+            _ => false,
+        }
+    }
+}
+
+// Like GL, a later binding for the same name overrides the earlier one.
+fn record_location_binding(bindings: &mut Vec<LocationBinding>, binding: LocationBinding) {
+    bindings.retain(|b| !binding.replaces(b));
+    bindings.push(binding);
+}
+
 #[derive(Debug)]
-pub struct ShaderProgram {
+pub struct ShaderProgram<S = Linked> where S: ProgramState {
     pub(crate) program_id: GLuint,
+    location_bindings: Vec<LocationBinding>,
+    uniform_sources: Option<Vec<String>>,
+    declared_uniforms: OnceCell<Option<Vec<DeclaredUniform>>>,
+    state_marker: PhantomData<S>,
 }
 
-impl Drop for ShaderProgram {
+impl<S> ShaderProgram<S> where S: ProgramState {
+    pub(crate) fn into_state<N>(mut self) -> ShaderProgram<N> where N: ProgramState {
+        let program_id = self.program_id;
+        let location_bindings = std::mem::take(&mut self.location_bindings);
+        let uniform_sources = self.uniform_sources.take();
+        let declared_uniforms = std::mem::take(&mut self.declared_uniforms);
+        std::mem::forget(self);
+
+        ShaderProgram {
+            program_id,
+            location_bindings,
+            uniform_sources,
+            declared_uniforms,
+            state_marker: PhantomData,
+        }
+    }
+
+    /// Sources to scan for declared uniforms instead of the attached
+    /// shaders, for builders that detach their shaders after linking.
+    pub(crate) fn set_uniform_sources(&mut self, sources: Vec<String>) {
+        self.uniform_sources = Some(sources);
+    }
+
+    /// The uniforms declared in the GLSL sources the program was linked
+    /// from, `None` when no source is available. Scanned on first use only,
+    /// since only failed uniform lookups need it.
+    pub(crate) fn declared_uniforms(&self) -> Option<&[DeclaredUniform]> {
+        self.declared_uniforms
+            .get_or_init(|| {
+                let attached;
+
+                let sources = match self.uniform_sources.as_ref() {
+                    Some(sources) => sources,
+                    None => {
+                        attached = attached_shader_sources(self).ok().flatten()?;
+                        &attached
+                    }
+                };
+
+                Some(sources.iter().flat_map(|source| crate::scan_declared_uniforms(source)).collect())
+            })
+            .as_deref()
+    }
+}
+
+impl<S> Drop for ShaderProgram<S> where S: ProgramState {
     fn drop(&mut self) {
         unsafe {
             gl::DeleteProgram(self.program_id);
@@ -95,6 +251,7 @@ pub enum ShaderProgramObjectParameter {
     ActiveAttributeMaxLength,
     ActiveUniforms,
     ActiveUniformMaxLength,
+    ProgramBinaryLength,
 }
 
 impl ShaderProgramObjectParameter {
@@ -111,21 +268,23 @@ impl ShaderProgramObjectParameter {
             }
             ShaderProgramObjectParameter::ActiveUniforms => gl::ACTIVE_UNIFORMS,
             ShaderProgramObjectParameter::ActiveUniformMaxLength => gl::ACTIVE_UNIFORM_MAX_LENGTH,
+            ShaderProgramObjectParameter::ProgramBinaryLength => gl::PROGRAM_BINARY_LENGTH,
         }
     }
 }
 
 ////////////////////////////////////////////////////////////////
 
-pub fn create_shader<T>() -> Shader<T> where T: ShaderType {
+pub fn create_shader<T>() -> Shader<T, Uncompiled> where T: ShaderType {
     let shader_id = unsafe { gl::CreateShader(T::to_gl_code()) };
     Shader {
         shader_id,
-        type_marker: PhantomData
+        type_marker: PhantomData,
+        state_marker: PhantomData,
     }
 }
 
-pub fn shader_source<T>(shader: &Shader<T>, source: &str) -> RGLResult<()> where T: ShaderType {
+pub fn shader_source<T>(shader: &Shader<T, Uncompiled>, source: &str) -> RGLResult<()> where T: ShaderType {
     use std::ffi::CString;
     use std::ptr::null;
 
@@ -143,35 +302,41 @@ pub fn shader_source<T>(shader: &Shader<T>, source: &str) -> RGLResult<()> where
 #[derive(Clone, Debug)]
 pub enum CompileShaderError {
     Standard(Vec<Error>),
-    Compile(String),
+    Compile(InfoLog),
 }
 
-pub fn compile_shader<T>(shader: &Shader<T>) -> Result<(), CompileShaderError> where T: ShaderType {
+pub fn compile_shader<T>(shader: Shader<T, Uncompiled>) -> Result<Shader<T, Compiled>, CompileShaderError> where T: ShaderType {
     unsafe {
         gl::CompileShader(shader.shader_id);
     }
 
+    check_compile_status(&shader)?;
+
+    Ok(shader.into_state())
+}
+
+pub(crate) fn check_compile_status<T, S>(shader: &Shader<T, S>) -> Result<(), CompileShaderError> where T: ShaderType, S: ShaderState {
     let se_map = |e| CompileShaderError::Standard(e);
 
-    let failure = get_shader_iv(&shader, ShaderObjectParameter::CompileStatus).map_err(se_map)? == 0;
+    let failure = get_shader_iv(shader, ShaderObjectParameter::CompileStatus).map_err(se_map)? == 0;
 
     if failure {
         let info_log_len =
-            get_shader_iv(&shader, ShaderObjectParameter::InfoLogLength).map_err(se_map)?;
+            get_shader_iv(shader, ShaderObjectParameter::InfoLogLength).map_err(se_map)?;
 
-        let log = if let Ok(success) = get_shader_info_log(&shader, info_log_len) {
+        let log = if let Ok(success) = get_shader_info_log(shader, info_log_len) {
             success
         } else {
             String::from("Failed to retrieve info log.")
         };
 
-        Err(CompileShaderError::Compile(log))
+        Err(CompileShaderError::Compile(InfoLog::parse(log)))
     } else {
         get_rgl_result(()).map_err(CompileShaderError::Standard)
     }
 }
 
-pub fn get_shader_iv<T>(shader: &Shader<T>, pname: ShaderObjectParameter) -> RGLResult<GLint> where T: ShaderType {
+pub fn get_shader_iv<T, S>(shader: &Shader<T, S>, pname: ShaderObjectParameter) -> RGLResult<GLint> where T: ShaderType, S: ShaderState {
     let mut result: GLint = 0;
 
     unsafe {
@@ -187,22 +352,23 @@ pub enum InfoLogError {
     Standard(Vec<Error>),
 }
 
-pub fn get_shader_info_log<T>(shader: &Shader<T>, len: GLsizei) -> Result<String, InfoLogError> where T: ShaderType {
+pub fn get_shader_info_log<T, S>(shader: &Shader<T, S>, len: GLsizei) -> Result<String, InfoLogError> where T: ShaderType, S: ShaderState {
     use std::ffi::CString;
-    use std::ptr::null_mut;
-
     if len > 0 {
-        let mut buffer: Vec<u8> = Vec::with_capacity(len as usize);
+        let mut buffer: Vec<u8> = vec![0; len as usize];
+        let mut written: GLsizei = 0;
 
         unsafe {
             gl::GetShaderInfoLog(
                 shader.shader_id,
                 len,
-                null_mut(),
+                &mut written,
                 buffer.as_mut_ptr() as *mut GLchar,
             );
         }
 
+        buffer.truncate(written.max(0) as usize);
+
         let c_str: CString = CString::new(buffer).map_err(|_| InfoLogError::InvalidLog)?;
         let data = c_str.into_string().map_err(|_| InfoLogError::InvalidLog)?;
         get_rgl_result(data).map_err(InfoLogError::Standard)
@@ -211,13 +377,19 @@ pub fn get_shader_info_log<T>(shader: &Shader<T>, len: GLsizei) -> Result<String
     }
 }
 
-pub fn create_program() -> ShaderProgram {
+pub fn create_program() -> ShaderProgram<Unlinked> {
     let program_id = unsafe { gl::CreateProgram() };
 
-    ShaderProgram { program_id }
+    ShaderProgram {
+        program_id,
+        location_bindings: vec![],
+        uniform_sources: None,
+        declared_uniforms: OnceCell::new(),
+        state_marker: PhantomData,
+    }
 }
 
-pub fn attach_shader<T>(program: &ShaderProgram, shader: &Shader<T>) -> RGLResult<()> where T: ShaderType {
+pub fn attach_shader<T>(program: &ShaderProgram<Unlinked>, shader: &Shader<T, Compiled>) -> RGLResult<()> where T: ShaderType {
     unsafe {
         gl::AttachShader(program.program_id, shader.shader_id);
     }
@@ -225,7 +397,7 @@ pub fn attach_shader<T>(program: &ShaderProgram, shader: &Shader<T>) -> RGLResul
     get_rgl_result(())
 }
 
-pub fn detach_shader<T>(program: &ShaderProgram,  shader: &Shader<T>) -> RGLResult<()> where T: ShaderType {
+pub fn detach_shader<T, S>(program: &ShaderProgram<S>, shader: &Shader<T, Compiled>) -> RGLResult<()> where T: ShaderType, S: ProgramState {
     unsafe {
         gl::DetachShader(program.program_id, shader.shader_id);
     }
@@ -233,42 +405,234 @@ pub fn detach_shader<T>(program: &ShaderProgram,  shader: &Shader<T>) -> RGLResu
     get_rgl_result(())
 }
 
+pub fn bind_attrib_location(program: &mut ShaderProgram<Unlinked>, location: GLuint, name: &str) -> RGLResult<()> {
+    use std::ffi::CString;
+
+    let c_name = CString::new(name).map_err(|_| vec![Error::InvalidValue])?;
+
+    unsafe {
+        gl::BindAttribLocation(program.program_id, location, c_name.as_ptr());
+    }
+
+    get_rgl_result(())?;
+
+    record_location_binding(&mut program.location_bindings, LocationBinding::Attrib {
+        name: name.to_string(),
+        location,
+    });
+
+    Ok(())
+}
+
+pub fn bind_frag_data_location(program: &mut ShaderProgram<Unlinked>, color_number: GLuint, name: &str) -> RGLResult<()> {
+    bind_frag_data_location_indexed(program, color_number, 0, name)
+}
+
+pub fn bind_frag_data_location_indexed(
+    program: &mut ShaderProgram<Unlinked>,
+    color_number: GLuint,
+    index: GLuint,
+    name: &str,
+) -> RGLResult<()> {
+    use std::ffi::CString;
+
+    let c_name = CString::new(name).map_err(|_| vec![Error::InvalidValue])?;
+
+    unsafe {
+        gl::BindFragDataLocationIndexed(program.program_id, color_number, index, c_name.as_ptr());
+    }
+
+    get_rgl_result(())?;
+
+    record_location_binding(&mut program.location_bindings, LocationBinding::FragData {
+        name: name.to_string(),
+        color_number,
+        index,
+    });
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LocationBindingMismatch {
+    Attrib {
+        name: String,
+        requested: GLuint,
+        actual: GLint,
+    },
+    FragData {
+        name: String,
+        requested: (GLuint, GLuint),
+        actual: (GLint, GLint),
+    },
+}
+
 #[derive(Clone, Debug)]
 pub enum LinkProgramError {
-    LinkFailure(String),
+    LinkFailure(InfoLog),
+    BindingMismatch(Vec<LocationBindingMismatch>),
     Standard(Vec<Error>),
 }
 
-pub fn link_program(program: &ShaderProgram) -> Result<(), LinkProgramError> {
+pub fn link_program(program: ShaderProgram<Unlinked>) -> Result<ShaderProgram<Linked>, LinkProgramError> {
     unsafe {
         gl::LinkProgram(program.program_id);
     }
 
+    check_link_status(&program)?;
+    check_location_bindings(&program)?;
+
+    Ok(program.into_state())
+}
+
+// Lets uniform lookups tell names the linker optimized out from names that
+// were never declared. Shaders without source, like SPIR-V ones, leave
+// nothing to compare against.
+fn attached_shader_sources<S>(program: &ShaderProgram<S>) -> RGLResult<Option<Vec<String>>> where S: ProgramState {
+    let count = get_program_iv(program, ShaderProgramObjectParameter::AttachedShaders)?;
+    let mut shaders: Vec<GLuint> = vec![0; count.max(0) as usize];
+    let mut written: GLsizei = 0;
+
+    unsafe {
+        gl::GetAttachedShaders(program.program_id, count, &mut written, shaders.as_mut_ptr());
+    }
+
+    shaders.truncate(written.max(0) as usize);
+
+    let mut sources = vec![];
+
+    for shader_id in shaders {
+        let mut length: GLint = 0;
+
+        unsafe {
+            gl::GetShaderiv(shader_id, gl::SHADER_SOURCE_LENGTH, &mut length);
+        }
+
+        if length <= 0 {
+            return get_rgl_result(None);
+        }
+
+        let mut buffer: Vec<u8> = vec![0; length as usize];
+        let mut source_written: GLsizei = 0;
+
+        unsafe {
+            gl::GetShaderSource(shader_id, length, &mut source_written, buffer.as_mut_ptr() as *mut GLchar);
+        }
+
+        buffer.truncate(source_written.max(0) as usize);
+        sources.push(String::from_utf8_lossy(&buffer).into_owned());
+    }
+
+    let result = if sources.is_empty() { None } else { Some(sources) };
+
+    get_rgl_result(result)
+}
+
+// Bindings for names the linker dropped as inactive have nothing to take
+// effect on, so only active variables are compared.
+fn check_location_bindings<S>(program: &ShaderProgram<S>) -> Result<(), LinkProgramError> where S: ProgramState {
+    use std::ffi::CString;
+
+    let mut mismatches = vec![];
+
+    for binding in program.location_bindings.iter() {
+        match binding {
+            LocationBinding::Attrib { name, location } => {
+                let c_name = CString::new(name.as_str()).expect("rgl: Binding name was validated on bind.");
+                let actual = unsafe { gl::GetAttribLocation(program.program_id, c_name.as_ptr()) };
+
+                if actual >= 0 && actual != *location as GLint {
+                    mismatches.push(LocationBindingMismatch::Attrib {
+                        name: name.clone(),
+                        requested: *location,
+                        actual,
+                    });
+                }
+            }
+            LocationBinding::FragData { name, color_number, index } => {
+                let c_name = CString::new(name.as_str()).expect("rgl: Binding name was validated on bind.");
+                let actual_location = unsafe { gl::GetFragDataLocation(program.program_id, c_name.as_ptr()) };
+                let actual_index = unsafe { gl::GetFragDataIndex(program.program_id, c_name.as_ptr()) };
+
+                if actual_location >= 0
+                    && (actual_location != *color_number as GLint || actual_index != *index as GLint) {
+                    mismatches.push(LocationBindingMismatch::FragData {
+                        name: name.clone(),
+                        requested: (*color_number, *index),
+                        actual: (actual_location, actual_index),
+                    });
+                }
+            }
+        }
+    }
+
+    get_rgl_result(()).map_err(LinkProgramError::Standard)?;
+
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(LinkProgramError::BindingMismatch(mismatches))
+    }
+}
+
+pub(crate) fn check_link_status<S>(program: &ShaderProgram<S>) -> Result<(), LinkProgramError> where S: ProgramState {
     let se_map = |e| LinkProgramError::Standard(e);
 
     let failure =
-        get_program_iv(&program, ShaderProgramObjectParameter::LinkStatus).map_err(se_map)? == 0;
+        get_program_iv(program, ShaderProgramObjectParameter::LinkStatus).map_err(se_map)? == 0;
 
     if failure {
         let log_len =
-            get_program_iv(&program, ShaderProgramObjectParameter::InfoLogLength).map_err(se_map)?;
+            get_program_iv(program, ShaderProgramObjectParameter::InfoLogLength).map_err(se_map)?;
 
-        let log = if let Ok(success) = get_program_info_log(&program, log_len) {
+        let log = if let Ok(success) = get_program_info_log(program, log_len) {
             success
         } else {
             String::from("rgl: Failed to get program info log.")
         };
 
-        Err(LinkProgramError::LinkFailure(log))
+        Err(LinkProgramError::LinkFailure(InfoLog::parse(log)))
     } else {
         get_rgl_result(()).map_err(LinkProgramError::Standard)
     }
 }
 
-pub fn get_program_iv(
-    program: &ShaderProgram,
+#[derive(Clone, Debug)]
+pub enum ValidateProgramError {
+    ValidationFailure(InfoLog),
+    Standard(Vec<Error>),
+}
+
+pub fn validate_program(program: &ShaderProgram) -> Result<(), ValidateProgramError> {
+    unsafe {
+        gl::ValidateProgram(program.program_id);
+    }
+
+    let se_map = |e| ValidateProgramError::Standard(e);
+
+    let failure =
+        get_program_iv(program, ShaderProgramObjectParameter::ValidateStatus).map_err(se_map)? == 0;
+
+    if failure {
+        let log_len =
+            get_program_iv(program, ShaderProgramObjectParameter::InfoLogLength).map_err(se_map)?;
+
+        let log = if let Ok(success) = get_program_info_log(program, log_len) {
+            success
+        } else {
+            String::from("rgl: Failed to get program info log.")
+        };
+
+        Err(ValidateProgramError::ValidationFailure(InfoLog::parse(log)))
+    } else {
+        get_rgl_result(()).map_err(ValidateProgramError::Standard)
+    }
+}
+
+pub fn get_program_iv<S>(
+    program: &ShaderProgram<S>,
     pname: ShaderProgramObjectParameter,
-) -> RGLResult<GLint> {
+) -> RGLResult<GLint> where S: ProgramState {
     let mut result: GLint = 0;
 
     unsafe {
@@ -278,22 +642,23 @@ pub fn get_program_iv(
     get_rgl_result(result)
 }
 
-pub fn get_program_info_log(program: &ShaderProgram, len: GLsizei) -> Result<String, InfoLogError> {
+pub fn get_program_info_log<S>(program: &ShaderProgram<S>, len: GLsizei) -> Result<String, InfoLogError> where S: ProgramState {
     use std::ffi::CString;
-    use std::ptr::null_mut;
-
     if len > 0 {
-        let mut buffer: Vec<u8> = Vec::with_capacity(len as usize);
+        let mut buffer: Vec<u8> = vec![0; len as usize];
+        let mut written: GLsizei = 0;
 
         unsafe {
             gl::GetProgramInfoLog(
                 program.program_id,
                 len,
-                null_mut(),
+                &mut written,
                 buffer.as_mut_ptr() as *mut GLchar,
             );
         }
 
+        buffer.truncate(written.max(0) as usize);
+
         let c_str = CString::new(buffer).map_err(|_| InfoLogError::InvalidLog)?;
         let data = c_str.into_string().map_err(|_| InfoLogError::InvalidLog)?;
         get_rgl_result(data).map_err(InfoLogError::Standard)
@@ -309,3 +674,27 @@ pub fn use_program(program: &ShaderProgram) -> RGLResult<()> {
 
     get_rgl_result(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn later_location_bindings_replace_earlier_ones() {
+        let mut bindings = vec![];
+
+        let attrib = |name: &str, location| LocationBinding::Attrib { name: name.to_string(), location };
+        let frag_data = |name: &str, color_number| LocationBinding::FragData {
+            name: name.to_string(),
+            color_number,
+            index: 0,
+        };
+
+        record_location_binding(&mut bindings, attrib("pos", 0));
+        record_location_binding(&mut bindings, frag_data("pos", 2));
+        record_location_binding(&mut bindings, attrib("normal", 1));
+        record_location_binding(&mut bindings, attrib("pos", 3));
+
+        assert_eq!(bindings, vec![frag_data("pos", 2), attrib("normal", 1), attrib("pos", 3)]);
+    }
+}
@@ -0,0 +1,25 @@
+use crate::{get_program_resource_index, get_rgl_result, ProgramInterface, RGLResult, ShaderProgram};
+use gl::types::*;
+
+////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ShaderStorageBlockIndex {
+    pub(crate) index: GLuint,
+}
+
+pub fn get_shader_storage_block_index(program: &ShaderProgram, name: &str) -> RGLResult<Option<ShaderStorageBlockIndex>> {
+    let index = get_program_resource_index(program, ProgramInterface::ShaderStorageBlock, name)?;
+
+    Ok(index.map(|index| ShaderStorageBlockIndex { index }))
+}
+
+/// Makes the block use the buffer bound to shader storage binding point
+/// `binding` with `bind_buffer_base` or `bind_buffer_range`.
+pub fn shader_storage_block_binding(program: &ShaderProgram, block: ShaderStorageBlockIndex, binding: GLuint) -> RGLResult<()> {
+    unsafe {
+        gl::ShaderStorageBlockBinding(program.program_id, block.index, binding);
+    }
+
+    get_rgl_result(())
+}
@@ -0,0 +1,150 @@
+use crate::{
+    check_compile_status, get_rgl_result, CompileShaderError, Compiled, Error, Shader, ShaderType,
+    Uncompiled,
+};
+use gl::types::*;
+use std::ffi::c_void;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+// Core in GL 4.6, which the gl crate's generated bindings predate.
+const SHADER_BINARY_FORMAT_SPIR_V: GLenum = 0x9551;
+
+type SpecializeShaderFn = extern "system" fn(GLuint, *const GLchar, GLuint, *const GLuint, *const GLuint);
+
+static SPECIALIZE_SHADER: AtomicPtr<c_void> = AtomicPtr::new(ptr::null_mut());
+
+pub(crate) fn load_with(loader: &mut dyn FnMut(&'static str) -> *const c_void) {
+    let mut fn_ptr = loader("glSpecializeShader");
+
+    if fn_ptr.is_null() {
+        fn_ptr = loader("glSpecializeShaderARB");
+    }
+
+    SPECIALIZE_SHADER.store(fn_ptr as *mut c_void, Ordering::Release);
+}
+
+pub fn is_specialize_shader_loaded() -> bool {
+    !SPECIALIZE_SHADER.load(Ordering::Acquire).is_null()
+}
+
+////////////////////////////////////////////////////////////////////
+
+mod private {
+    pub trait PrivSpecializationConstantValue {}
+
+    impl PrivSpecializationConstantValue for bool {}
+    impl PrivSpecializationConstantValue for i32 {}
+    impl PrivSpecializationConstantValue for u32 {}
+    impl PrivSpecializationConstantValue for f32 {}
+}
+
+pub trait SpecializationConstantValue : private::PrivSpecializationConstantValue {
+    fn to_bits(self) -> GLuint;
+}
+
+impl SpecializationConstantValue for bool {
+    fn to_bits(self) -> GLuint {
+        self as GLuint
+    }
+}
+
+impl SpecializationConstantValue for i32 {
+    fn to_bits(self) -> GLuint {
+        self as GLuint
+    }
+}
+
+impl SpecializationConstantValue for u32 {
+    fn to_bits(self) -> GLuint {
+        self
+    }
+}
+
+impl SpecializationConstantValue for f32 {
+    fn to_bits(self) -> GLuint {
+        f32::to_bits(self)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpecializationConstant {
+    pub index: GLuint,
+    pub value: GLuint,
+}
+
+impl SpecializationConstant {
+    pub fn new<V>(index: GLuint, value: V) -> SpecializationConstant where V: SpecializationConstantValue {
+        SpecializationConstant {
+            index,
+            value: value.to_bits(),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+
+/// Loads a SPIR-V module into `shader`. Failures are reported the same way
+/// as `specialize_shader`, so both steps can be chained with `?`.
+pub fn shader_binary_spirv<T>(shader: &Shader<T, Uncompiled>, binary: &[u8]) -> Result<(), CompileShaderError> where T: ShaderType {
+    // SPIR-V modules are a stream of 32-bit words.
+    if binary.is_empty() || !binary.len().is_multiple_of(4) {
+        return Err(CompileShaderError::Standard(vec![Error::InvalidValue]));
+    }
+
+    unsafe {
+        gl::ShaderBinary(
+            1,
+            &shader.shader_id,
+            SHADER_BINARY_FORMAT_SPIR_V,
+            binary.as_ptr() as *const c_void,
+            binary.len() as GLsizei,
+        );
+    }
+
+    get_rgl_result(()).map_err(CompileShaderError::Standard)
+}
+
+/// Specializes a shader loaded with `shader_binary_spirv`, compiling it.
+///
+/// The gl crate has no binding for glSpecializeShader, so it is loaded by
+/// `rgl::load_with`. If only `gl::load_with` was called this fails with
+/// `InvalidOperation`; `is_specialize_shader_loaded` tells whether it is
+/// available.
+pub fn specialize_shader<T>(
+    shader: Shader<T, Uncompiled>,
+    entry_point: &str,
+    constants: &[SpecializationConstant],
+) -> Result<Shader<T, Compiled>, CompileShaderError> where T: ShaderType {
+    use std::ffi::CString;
+    use std::mem;
+
+    let fn_ptr = SPECIALIZE_SHADER.load(Ordering::Acquire);
+
+    // Without glSpecializeShader the shader can never reach a compiled state.
+    if fn_ptr.is_null() {
+        return Err(CompileShaderError::Standard(vec![Error::InvalidOperation]));
+    }
+
+    let c_entry_point = CString::new(entry_point)
+        .map_err(|_| CompileShaderError::Standard(vec![Error::InvalidValue]))?;
+
+    let indices: Vec<GLuint> = constants.iter().map(|c| c.index).collect();
+    let values: Vec<GLuint> = constants.iter().map(|c| c.value).collect();
+
+    unsafe {
+        let specialize: SpecializeShaderFn = mem::transmute(fn_ptr);
+
+        specialize(
+            shader.shader_id,
+            c_entry_point.as_ptr(),
+            constants.len() as GLuint,
+            indices.as_ptr(),
+            values.as_ptr(),
+        );
+    }
+
+    check_compile_status(&shader)?;
+
+    Ok(shader.into_state())
+}
@@ -0,0 +1,265 @@
+use crate::{get_rgl_result, Error, RGLResult, ShaderProgram, ShaderType};
+use gl::types::*;
+use std::ffi::CString;
+use std::marker::PhantomData;
+
+////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub struct SubroutineIndex<T> where T: ShaderType {
+    pub(crate) index: GLuint,
+    stage_marker: PhantomData<T>,
+}
+
+impl<T> Clone for SubroutineIndex<T> where T: ShaderType {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SubroutineIndex<T> where T: ShaderType {}
+
+#[derive(Debug)]
+pub struct SubroutineUniformLocation<T> where T: ShaderType {
+    pub(crate) loc: GLint,
+    stage_marker: PhantomData<T>,
+}
+
+impl<T> Clone for SubroutineUniformLocation<T> where T: ShaderType {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SubroutineUniformLocation<T> where T: ShaderType {}
+
+pub fn get_subroutine_index<T>(program: &ShaderProgram, name: &str) -> RGLResult<Option<SubroutineIndex<T>>> where T: ShaderType {
+    let c_name = CString::new(name).map_err(|_| vec![Error::InvalidValue])?;
+
+    let index = unsafe { gl::GetSubroutineIndex(program.program_id, T::to_gl_code(), c_name.as_ptr()) };
+
+    let result = if index == gl::INVALID_INDEX {
+        None
+    } else {
+        Some(SubroutineIndex {
+            index,
+            stage_marker: PhantomData,
+        })
+    };
+
+    get_rgl_result(result)
+}
+
+pub fn get_subroutine_uniform_location<T>(program: &ShaderProgram, name: &str) -> RGLResult<Option<SubroutineUniformLocation<T>>> where T: ShaderType {
+    let c_name = CString::new(name).map_err(|_| vec![Error::InvalidValue])?;
+
+    let loc = unsafe { gl::GetSubroutineUniformLocation(program.program_id, T::to_gl_code(), c_name.as_ptr()) };
+
+    let result = if loc < 0 {
+        None
+    } else {
+        Some(SubroutineUniformLocation {
+            loc,
+            stage_marker: PhantomData,
+        })
+    };
+
+    get_rgl_result(result)
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum ProgramStageParameter {
+    ActiveSubroutines,
+    ActiveSubroutineUniforms,
+    ActiveSubroutineUniformLocations,
+    ActiveSubroutineMaxLength,
+    ActiveSubroutineUniformMaxLength,
+}
+
+impl ProgramStageParameter {
+    fn to_gl_code(self) -> GLenum {
+        match self {
+            ProgramStageParameter::ActiveSubroutines => gl::ACTIVE_SUBROUTINES,
+            ProgramStageParameter::ActiveSubroutineUniforms => gl::ACTIVE_SUBROUTINE_UNIFORMS,
+            ProgramStageParameter::ActiveSubroutineUniformLocations => gl::ACTIVE_SUBROUTINE_UNIFORM_LOCATIONS,
+            ProgramStageParameter::ActiveSubroutineMaxLength => gl::ACTIVE_SUBROUTINE_MAX_LENGTH,
+            ProgramStageParameter::ActiveSubroutineUniformMaxLength => gl::ACTIVE_SUBROUTINE_UNIFORM_MAX_LENGTH,
+        }
+    }
+}
+
+pub fn get_program_stage_iv<T>(program: &ShaderProgram, pname: ProgramStageParameter) -> RGLResult<GLint> where T: ShaderType {
+    let mut result: GLint = 0;
+
+    unsafe {
+        gl::GetProgramStageiv(program.program_id, T::to_gl_code(), pname.to_gl_code(), &mut result);
+    }
+
+    get_rgl_result(result)
+}
+
+fn get_active_subroutine_uniform_iv<T>(program: &ShaderProgram, index: GLuint, pname: GLenum, count: usize) -> RGLResult<Vec<GLint>> where T: ShaderType {
+    let mut values: Vec<GLint> = vec![0; count.max(1)];
+
+    unsafe {
+        gl::GetActiveSubroutineUniformiv(program.program_id, T::to_gl_code(), index, pname, values.as_mut_ptr());
+    }
+
+    values.truncate(count);
+
+    get_rgl_result(values)
+}
+
+fn get_active_subroutine_uniform_name<T>(program: &ShaderProgram, index: GLuint, max_length: GLint) -> RGLResult<String> where T: ShaderType {
+    let mut buffer: Vec<u8> = vec![0; max_length.max(1) as usize];
+    let mut written: GLsizei = 0;
+
+    unsafe {
+        gl::GetActiveSubroutineUniformName(
+            program.program_id,
+            T::to_gl_code(),
+            index,
+            buffer.len() as GLsizei,
+            &mut written,
+            buffer.as_mut_ptr() as *mut GLchar,
+        );
+    }
+
+    buffer.truncate(written.max(0) as usize);
+
+    get_rgl_result(String::from_utf8_lossy(&buffer).into_owned())
+}
+
+////////////////////////////////////////////////////////////////////
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SubroutineError {
+    InvalidLocation(GLint),
+    Incompatible { location: GLint, index: GLuint },
+    Unset(Vec<GLint>),
+    /// The selection was made for a program other than the one in use.
+    ProgramNotCurrent { expected: GLuint, current: GLuint },
+    Standard(Vec<Error>),
+}
+
+/// The subroutine chosen for every subroutine uniform of one stage. GL
+/// requires all of them to be set in a single call, and forgets the choice
+/// whenever `use_program` is called.
+#[derive(Clone, Debug)]
+pub struct SubroutineSelection<T> where T: ShaderType {
+    program_id: GLuint,
+    indices: Vec<Option<GLuint>>,
+    compatible: Vec<Vec<GLuint>>,
+    stage_marker: PhantomData<T>,
+}
+
+impl<T> SubroutineSelection<T> where T: ShaderType {
+    pub fn new(program: &ShaderProgram) -> RGLResult<SubroutineSelection<T>> {
+        let locations = get_program_stage_iv::<T>(program, ProgramStageParameter::ActiveSubroutineUniformLocations)?;
+        let uniforms = get_program_stage_iv::<T>(program, ProgramStageParameter::ActiveSubroutineUniforms)?;
+        let max_length = get_program_stage_iv::<T>(program, ProgramStageParameter::ActiveSubroutineUniformMaxLength)?;
+
+        let mut compatible = vec![vec![]; locations.max(0) as usize];
+
+        for index in 0..uniforms.max(0) as GLuint {
+            let count = get_active_subroutine_uniform_iv::<T>(program, index, gl::NUM_COMPATIBLE_SUBROUTINES, 1)?[0];
+            let size = get_active_subroutine_uniform_iv::<T>(program, index, gl::UNIFORM_SIZE, 1)?[0];
+            let subroutines: Vec<GLuint> =
+                get_active_subroutine_uniform_iv::<T>(program, index, gl::COMPATIBLE_SUBROUTINES, count.max(0) as usize)?
+                    .iter()
+                    .map(|i| *i as GLuint)
+                    .collect();
+
+            let name = get_active_subroutine_uniform_name::<T>(program, index, max_length)?;
+
+            // Arrays of subroutine uniforms occupy consecutive locations.
+            if let Some(base) = get_subroutine_uniform_location::<T>(program, &name)? {
+                for element in 0..size.max(1) {
+                    if let Some(slot) = compatible.get_mut((base.loc + element) as usize) {
+                        *slot = subroutines.clone();
+                    }
+                }
+            }
+        }
+
+        Ok(SubroutineSelection {
+            program_id: program.program_id,
+            indices: vec![None; compatible.len()],
+            compatible,
+            stage_marker: PhantomData,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    pub fn select(
+        &mut self,
+        location: SubroutineUniformLocation<T>,
+        subroutine: SubroutineIndex<T>,
+    ) -> Result<&mut SubroutineSelection<T>, SubroutineError> {
+        let slot = location.loc as usize;
+
+        let compatible = self
+            .compatible
+            .get(slot)
+            .ok_or(SubroutineError::InvalidLocation(location.loc))?;
+
+        if !compatible.contains(&subroutine.index) {
+            return Err(SubroutineError::Incompatible {
+                location: location.loc,
+                index: subroutine.index,
+            });
+        }
+
+        self.indices[slot] = Some(subroutine.index);
+
+        Ok(self)
+    }
+}
+
+/// Sets every subroutine uniform of the stage for the current program, which
+/// has to be the one the selection was made for.
+pub fn uniform_subroutines<T>(selection: &SubroutineSelection<T>) -> Result<(), SubroutineError> where T: ShaderType {
+    let mut current: GLint = 0;
+
+    unsafe {
+        gl::GetIntegerv(gl::CURRENT_PROGRAM, &mut current);
+    }
+
+    get_rgl_result(()).map_err(SubroutineError::Standard)?;
+
+    // Indices and locations are only meaningful for the program they were
+    // queried from.
+    if current as GLuint != selection.program_id {
+        return Err(SubroutineError::ProgramNotCurrent {
+            expected: selection.program_id,
+            current: current as GLuint,
+        });
+    }
+
+    let unset: Vec<GLint> = selection
+        .indices
+        .iter()
+        .enumerate()
+        .filter(|(_, index)| index.is_none())
+        .map(|(location, _)| location as GLint)
+        .collect();
+
+    if !unset.is_empty() {
+        return Err(SubroutineError::Unset(unset));
+    }
+
+    let indices: Vec<GLuint> = selection.indices.iter().flatten().copied().collect();
+
+    unsafe {
+        gl::UniformSubroutinesuiv(T::to_gl_code(), indices.len() as GLsizei, indices.as_ptr());
+    }
+
+    get_rgl_result(()).map_err(SubroutineError::Standard)
+}
@@ -0,0 +1,235 @@
+use crate::{
+    get_rgl_result, BufferObject, BufferUsage, Error, Lines, Points, RGLResult, ShaderProgram,
+    TransformFeedbackBuffer, Triangles, Unlinked,
+};
+use gl::types::*;
+use std::marker::PhantomData;
+
+////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TransformFeedbackBufferMode {
+    InterleavedAttribs,
+    SeparateAttribs,
+}
+
+impl TransformFeedbackBufferMode {
+    fn to_gl_code(self) -> GLenum {
+        match self {
+            TransformFeedbackBufferMode::InterleavedAttribs => gl::INTERLEAVED_ATTRIBS,
+            TransformFeedbackBufferMode::SeparateAttribs => gl::SEPARATE_ATTRIBS,
+        }
+    }
+}
+
+pub fn transform_feedback_varyings(
+    program: &ShaderProgram<Unlinked>,
+    varyings: &[&str],
+    mode: TransformFeedbackBufferMode,
+) -> RGLResult<()> {
+    use std::ffi::CString;
+
+    let c_varyings = varyings
+        .iter()
+        .map(|v| CString::new(*v))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| vec![Error::InvalidValue])?;
+
+    let ptrs: Vec<*const GLchar> = c_varyings.iter().map(|v| v.as_ptr()).collect();
+
+    unsafe {
+        gl::TransformFeedbackVaryings(
+            program.program_id,
+            ptrs.len() as GLsizei,
+            ptrs.as_ptr(),
+            mode.to_gl_code(),
+        );
+    }
+
+    get_rgl_result(())
+}
+
+////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub struct TransformFeedback {
+    pub(crate) tf_id: GLuint,
+}
+
+impl Drop for TransformFeedback {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTransformFeedbacks(1, &self.tf_id);
+        }
+    }
+}
+
+pub fn create_transform_feedbacks(num: GLint) -> RGLResult<Vec<TransformFeedback>> {
+    if num < 1 {
+        panic!(
+            "rgl: Invalid parameter {} to glCreateTransformFeedbacks, must be 1 or greater.",
+            num
+        );
+    }
+
+    let mut ids = vec![0u32; num as usize];
+
+    unsafe {
+        gl::CreateTransformFeedbacks(num, ids.as_mut_ptr());
+    }
+
+    let result = ids
+        .drain(..)
+        .map(|tf_id| TransformFeedback { tf_id })
+        .collect();
+    get_rgl_result(result)
+}
+
+pub fn bind_transform_feedback(tf_option: Option<&TransformFeedback>) -> RGLResult<()> {
+    let tf_id = tf_option.map_or(0, |tf| tf.tf_id);
+
+    unsafe {
+        gl::BindTransformFeedback(gl::TRANSFORM_FEEDBACK, tf_id);
+    }
+
+    get_rgl_result(())
+}
+
+pub fn transform_feedback_buffer_base<K, U>(
+    tf: &TransformFeedback,
+    index: GLuint,
+    buffer: &BufferObject<TransformFeedbackBuffer, K, U>,
+) -> RGLResult<()> where U: BufferUsage {
+    unsafe {
+        gl::TransformFeedbackBufferBase(tf.tf_id, index, buffer.buffer_id);
+    }
+
+    get_rgl_result(())
+}
+
+pub fn transform_feedback_buffer_range<K, U>(
+    tf: &TransformFeedback,
+    index: GLuint,
+    buffer: &BufferObject<TransformFeedbackBuffer, K, U>,
+    offset: GLintptr,
+    size: GLsizeiptr,
+) -> RGLResult<()> where U: BufferUsage {
+    // Captured data is written in 4 byte units.
+    if offset % 4 != 0 || size % 4 != 0 {
+        return Err(vec![Error::InvalidValue]);
+    }
+
+    unsafe {
+        gl::TransformFeedbackBufferRange(tf.tf_id, index, buffer.buffer_id, offset, size);
+    }
+
+    get_rgl_result(())
+}
+
+////////////////////////////////////////////////////////////////////
+
+mod private {
+
+    use crate::{Points, Lines, Triangles};
+
+    pub trait PrivTransformFeedbackPrimitive {}
+
+    impl PrivTransformFeedbackPrimitive for Points {}
+    impl PrivTransformFeedbackPrimitive for Lines {}
+    impl PrivTransformFeedbackPrimitive for Triangles {}
+}
+
+pub trait TransformFeedbackPrimitive : private::PrivTransformFeedbackPrimitive {
+    fn to_gl_code() -> GLenum;
+}
+
+impl TransformFeedbackPrimitive for Points {
+    fn to_gl_code() -> GLenum {
+        gl::POINTS
+    }
+}
+
+impl TransformFeedbackPrimitive for Lines {
+    fn to_gl_code() -> GLenum {
+        gl::LINES
+    }
+}
+
+impl TransformFeedbackPrimitive for Triangles {
+    fn to_gl_code() -> GLenum {
+        gl::TRIANGLES
+    }
+}
+
+/// An active capture into the buffers of a `TransformFeedback`. Capture
+/// ends when the scope is ended or dropped.
+#[derive(Debug)]
+pub struct TransformFeedbackScope<'a> {
+    paused: bool,
+    ended: bool,
+    tf_marker: PhantomData<&'a TransformFeedback>,
+}
+
+pub fn begin_transform_feedback<P>(tf: &TransformFeedback) -> RGLResult<TransformFeedbackScope<'_>> where P: TransformFeedbackPrimitive {
+    bind_transform_feedback(Some(tf))?;
+
+    unsafe {
+        gl::BeginTransformFeedback(P::to_gl_code());
+    }
+
+    // The scope ends the capture when dropped, so it must not exist unless
+    // the capture actually began.
+    get_rgl_result(())?;
+
+    Ok(TransformFeedbackScope {
+        paused: false,
+        ended: false,
+        tf_marker: PhantomData,
+    })
+}
+
+impl<'a> TransformFeedbackScope<'a> {
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self) -> RGLResult<()> {
+        unsafe {
+            gl::PauseTransformFeedback();
+        }
+
+        get_rgl_result(())?;
+        self.paused = true;
+        Ok(())
+    }
+
+    pub fn resume(&mut self) -> RGLResult<()> {
+        unsafe {
+            gl::ResumeTransformFeedback();
+        }
+
+        get_rgl_result(())?;
+        self.paused = false;
+        Ok(())
+    }
+
+    pub fn end(mut self) -> RGLResult<()> {
+        self.ended = true;
+
+        unsafe {
+            gl::EndTransformFeedback();
+        }
+
+        get_rgl_result(())
+    }
+}
+
+impl<'a> Drop for TransformFeedbackScope<'a> {
+    fn drop(&mut self) {
+        if !self.ended {
+            unsafe {
+                gl::EndTransformFeedback();
+            }
+        }
+    }
+}
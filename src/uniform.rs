@@ -1,411 +1,876 @@
 use crate::get_rgl_result;
+use crate::AttributeVector;
+use crate::Error;
+use crate::GlslType;
 use crate::RGLResult;
 use crate::ShaderProgram;
 use gl::types::*;
-use std::ffi::CStr;
+use std::collections::{HashMap, HashSet};
+use std::ffi::CString;
+use std::marker::PhantomData;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct UniformLocation {
     pub(crate) loc: GLint,
 }
 
-pub fn get_uniform_location(program: ShaderProgram, name: &CStr) -> RGLResult<UniformLocation> {
-    let loc = unsafe { gl::GetUniformLocation(program.program_id, name.as_ptr()) };
+#[derive(Clone, Debug, PartialEq)]
+pub enum UniformLookupError {
+    /// Nothing by that name is declared. `suggestions` holds close matches
+    /// among the program's active uniforms.
+    NotFound { name: String, suggestions: Vec<String> },
+    /// Declared in the sources but removed by the linker as unused.
+    OptimizedOut { name: String },
+    /// Members of uniform blocks have no location, they are set through the
+    /// block's buffer.
+    BlockMember { name: String, block: String },
+    Standard(Vec<Error>),
+}
 
-    let location = UniformLocation { loc };
+pub fn get_uniform_location(program: &ShaderProgram, name: &str) -> Result<UniformLocation, UniformLookupError> {
+    let normalized = normalize_uniform_path(name).ok_or_else(|| UniformLookupError::Standard(vec![Error::InvalidValue]))?;
+    let c_name = CString::new(normalized.as_str()).expect("rgl: Normalized uniform paths contain no NUL.");
 
-    get_rgl_result(location)
-}
+    let loc = unsafe { gl::GetUniformLocation(program.program_id, c_name.as_ptr()) };
 
-pub fn uniform1f(location: UniformLocation, v0: f32) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform1f(location.loc, v0);
+    get_rgl_result(()).map_err(UniformLookupError::Standard)?;
+
+    if loc >= 0 {
+        Ok(UniformLocation { loc })
+    } else {
+        Err(missing_uniform(program, &normalized))
     }
+}
 
-    get_rgl_result(())
+fn path_root(path: &str) -> &str {
+    &path[..path.find(['.', '[']).unwrap_or(path.len())]
 }
 
-pub fn uniform2f(location: UniformLocation, v0: f32, v1: f32) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform2f(location.loc, v0, v1);
+fn missing_uniform(program: &ShaderProgram, name: &str) -> UniformLookupError {
+    let root = path_root(name);
+    let declared = program.declared_uniforms();
+
+    if let Some(block) = declared.into_iter().flatten().find(|d| d.name == root).and_then(|d| d.block.as_ref()) {
+        return UniformLookupError::BlockMember {
+            name: name.to_owned(),
+            block: block.clone(),
+        };
     }
 
-    get_rgl_result(())
-}
+    // Block members are active too, but only default block uniforms have
+    // locations.
+    let active: Vec<String> = match crate::get_active_uniforms(program) {
+        Ok(uniforms) => uniforms
+            .into_iter()
+            .filter(|u| u.location >= 0)
+            .map(|u| normalize_uniform_path(&u.name).unwrap_or(u.name))
+            .collect(),
+        Err(e) => return UniformLookupError::Standard(e),
+    };
 
-pub fn uniform3f(location: UniformLocation, v0: f32, v1: f32, v2: f32) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform3f(location.loc, v0, v1, v2);
-    }
+    let active_root = active.iter().any(|a| path_root(a) == root);
+    let declared_root = declared.is_some_and(|d| d.iter().any(|d| d.name == root));
 
-    get_rgl_result(())
+    if declared_root && !active_root {
+        UniformLookupError::OptimizedOut { name: name.to_owned() }
+    } else {
+        UniformLookupError::NotFound {
+            name: name.to_owned(),
+            suggestions: close_matches(name, &active),
+        }
+    }
 }
 
-pub fn uniform4f(location: UniformLocation, v0: f32, v1: f32, v2: f32, v3: f32) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform4f(location.loc, v0, v1, v2, v3);
-    }
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
 
-    get_rgl_result(())
-}
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
 
-pub fn uniform1i(location: UniformLocation, v0: i32) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform1i(location.loc, v0);
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + (ca != *cb) as usize;
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
     }
 
-    get_rgl_result(())
+    row[b.len()]
 }
 
-pub fn uniform2i(location: UniformLocation, v0: i32, v1: i32) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform2i(location.loc, v0, v1);
-    }
+fn close_matches(name: &str, candidates: &[String]) -> Vec<String> {
+    let threshold = (name.len() / 3).max(2);
 
-    get_rgl_result(())
+    let mut matches: Vec<(usize, &String)> = candidates
+        .iter()
+        .map(|c| (edit_distance(name, c), c))
+        .filter(|(distance, _)| *distance <= threshold)
+        .collect();
+
+    matches.sort();
+    matches.dedup_by(|a, b| a.1 == b.1);
+    matches.into_iter().take(3).map(|(_, c)| c.clone()).collect()
 }
 
-pub fn uniform3i(location: UniformLocation, v0: i32, v1: i32, v2: i32) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform3i(location.loc, v0, v1, v2);
-    }
+/// Normalizes a uniform path such as `lights[3].color`. Whitespace and
+/// leading zeros are dropped, and a trailing `[0]` is removed since GL
+/// treats `name` and `name[0]` as the same uniform.
+pub fn normalize_uniform_path(path: &str) -> Option<String> {
+    let mut normalized = String::with_capacity(path.len());
 
-    get_rgl_result(())
-}
+    for (i, segment) in path.split('.').enumerate() {
+        let segment = segment.trim();
+        let (ident, mut rest) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+        let ident = ident.trim_end();
 
-pub fn uniform4i(location: UniformLocation, v0: i32, v1: i32, v2: i32, v3: i32) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform4i(location.loc, v0, v1, v2, v3);
-    }
+        let mut chars = ident.chars();
+        let valid_ident = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
 
-    get_rgl_result(())
-}
+        if !valid_ident {
+            return None;
+        }
 
-pub fn uniform1ui(location: UniformLocation, v0: u32) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform1ui(location.loc, v0);
-    }
+        if i > 0 {
+            normalized.push('.');
+        }
+        normalized.push_str(ident);
 
-    get_rgl_result(())
-}
+        while !rest.is_empty() {
+            let close = rest.find(']')?;
+            let index: u32 = rest[1..close].trim().parse().ok()?;
+            normalized.push_str(&format!("[{}]", index));
+            rest = rest[close + 1..].trim_start();
 
-pub fn uniform2ui(location: UniformLocation, v0: u32, v1: u32) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform2ui(location.loc, v0, v1);
+            if !rest.is_empty() && !rest.starts_with('[') {
+                return None;
+            }
+        }
     }
 
-    get_rgl_result(())
-}
-
-pub fn uniform3ui(location: UniformLocation, v0: u32, v1: u32, v2: u32) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform3ui(location.loc, v0, v1, v2);
+    if normalized.ends_with("[0]") {
+        normalized.truncate(normalized.len() - 3);
     }
 
-    get_rgl_result(())
+    Some(normalized)
 }
 
-pub fn uniform4ui(location: UniformLocation, v0: u32, v1: u32, v2: u32, v3: u32) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform4ui(location.loc, v0, v1, v2, v3);
-    }
+pub enum MissingUniformPolicy {
+    Error,
+    /// Hand the error to the callback the first time a name is missing and
+    /// carry on as if the lookup returned `None`.
+    WarnOnce(Box<dyn FnMut(&UniformLookupError)>),
+}
 
-    get_rgl_result(())
+/// Uniform locations of a program, queried once per name.
+pub struct UniformLocations<'p> {
+    program: &'p ShaderProgram,
+    policy: MissingUniformPolicy,
+    locations: HashMap<String, Result<UniformLocation, UniformLookupError>>,
+    warned: HashSet<String>,
 }
 
-pub fn uniform1fv(location: UniformLocation, data: &[f32]) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform1fv(
-            location.loc,
-            data.len() as GLsizei,
-            data.as_ptr() as *const GLfloat,
-        );
+impl<'p> UniformLocations<'p> {
+    pub fn new(program: &'p ShaderProgram) -> UniformLocations<'p> {
+        UniformLocations::with_policy(program, MissingUniformPolicy::Error)
     }
 
-    get_rgl_result(())
-}
+    pub fn with_policy(program: &'p ShaderProgram, policy: MissingUniformPolicy) -> UniformLocations<'p> {
+        UniformLocations {
+            program,
+            policy,
+            locations: HashMap::new(),
+            warned: HashSet::new(),
+        }
+    }
 
-pub fn uniform2fv(location: UniformLocation, data: &[f32]) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform2fv(
-            location.loc,
-            (data.len() / 2) as GLsizei,
-            data.as_ptr() as *const GLfloat,
-        );
+    pub fn program(&self) -> &'p ShaderProgram {
+        self.program
     }
 
-    get_rgl_result(())
-}
+    /// The location of `path`. Missing uniforms are an error under
+    /// `MissingUniformPolicy::Error` and `None` under `WarnOnce`.
+    pub fn get(&mut self, path: &str) -> Result<Option<UniformLocation>, UniformLookupError> {
+        let result = match self.locations.get(path) {
+            Some(result) => result.clone(),
+            None => {
+                let normalized = normalize_uniform_path(path)
+                    .ok_or_else(|| UniformLookupError::Standard(vec![Error::InvalidValue]))?;
 
-pub fn uniform3fv(location: UniformLocation, data: &[f32]) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform3fv(
-            location.loc,
-            (data.len() / 3) as GLsizei,
-            data.as_ptr() as *const GLfloat,
-        );
+                let result = match self.locations.get(&normalized) {
+                    Some(result) => result.clone(),
+                    None => get_uniform_location(self.program, &normalized),
+                };
+
+                if let Err(UniformLookupError::Standard(e)) = result {
+                    return Err(UniformLookupError::Standard(e));
+                }
+
+                if normalized != path {
+                    self.locations.insert(normalized, result.clone());
+                }
+                self.locations.insert(path.to_owned(), result.clone());
+
+                result
+            }
+        };
+
+        match result {
+            Ok(location) => Ok(Some(location)),
+            Err(e) => match &mut self.policy {
+                MissingUniformPolicy::Error => Err(e),
+                MissingUniformPolicy::WarnOnce(warn) => {
+                    if self.warned.insert(path.to_owned()) {
+                        warn(&e);
+                    }
+
+                    Ok(None)
+                }
+            },
+        }
     }
 
-    get_rgl_result(())
-}
+    pub fn len(&self) -> usize {
+        self.locations.len()
+    }
 
-pub fn uniform4fv(location: UniformLocation, data: &[f32]) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform4fv(
-            location.loc,
-            (data.len() / 4) as GLsizei,
-            data.as_ptr() as *const GLfloat,
-        );
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
     }
 
-    get_rgl_result(())
+    pub fn clear(&mut self) {
+        self.locations.clear();
+        self.warned.clear();
+    }
 }
 
-pub fn uniform1iv(location: UniformLocation, data: &[i32]) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform1iv(
-            location.loc,
-            data.len() as GLsizei,
-            data.as_ptr() as *const GLint,
-        );
+impl UniformLookupError {
+    /// A readable description of a missing uniform, for `WarnOnce`
+    /// callbacks. `None` for `Standard` errors.
+    pub fn message(&self) -> Option<String> {
+        match self {
+            UniformLookupError::NotFound { name, suggestions } if suggestions.is_empty() => {
+                Some(format!("No uniform named `{}`.", name))
+            }
+            UniformLookupError::NotFound { name, suggestions } => Some(format!(
+                "No uniform named `{}`, did you mean `{}`?",
+                name,
+                suggestions.join("`, `")
+            )),
+            UniformLookupError::OptimizedOut { name } => {
+                Some(format!("Uniform `{}` is declared but was optimized out by the linker.", name))
+            }
+            UniformLookupError::BlockMember { name, block } => {
+                Some(format!("Uniform `{}` is a member of block `{}` and has no location.", name, block))
+            }
+            UniformLookupError::Standard(_) => None,
+        }
     }
+}
 
-    get_rgl_result(())
+////////////////////////////////////////////////////////////////////
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct UniformBlockIndex {
+    pub(crate) index: GLuint,
 }
 
-pub fn uniform2iv(location: UniformLocation, data: &[i32]) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform2iv(
-            location.loc,
-            (data.len() / 2) as GLsizei,
-            data.as_ptr() as *const GLint,
-        );
-    }
+pub fn get_uniform_block_index(program: &ShaderProgram, name: &str) -> RGLResult<Option<UniformBlockIndex>> {
+    let c_name = CString::new(name).map_err(|_| vec![Error::InvalidValue])?;
+
+    let index = unsafe { gl::GetUniformBlockIndex(program.program_id, c_name.as_ptr()) };
+
+    let result = if index == gl::INVALID_INDEX { None } else { Some(UniformBlockIndex { index }) };
+
+    get_rgl_result(result)
+}
+
+/// Makes the block read from the buffer bound to uniform buffer binding
+/// point `binding` with `bind_buffer_base` or `bind_buffer_range`.
+pub fn uniform_block_binding(program: &ShaderProgram, block: UniformBlockIndex, binding: GLuint) -> RGLResult<()> {
+    unsafe {
+        gl::UniformBlockBinding(program.program_id, block.index, binding);
+    }
 
     get_rgl_result(())
 }
 
-pub fn uniform3iv(location: UniformLocation, data: &[i32]) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform3iv(
-            location.loc,
-            (data.len() / 3) as GLsizei,
-            data.as_ptr() as *const GLint,
-        );
-    }
+////////////////////////////////////////////////////////////////////
 
-    get_rgl_result(())
+/// The data of a uniform value, laid out as the matching `glUniform*v`
+/// call expects it. Matrices are column major unless `transpose` is set.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum UniformData<'a> {
+    Float { components: usize, data: &'a [f32] },
+    Int { components: usize, data: &'a [i32] },
+    UnsignedInt { components: usize, data: &'a [u32] },
+    Double { components: usize, data: &'a [f64] },
+    FloatMatrix { columns: usize, rows: usize, transpose: bool, data: &'a [f32] },
+    DoubleMatrix { columns: usize, rows: usize, transpose: bool, data: &'a [f64] },
 }
 
-pub fn uniform4iv(location: UniformLocation, data: &[i32]) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform4iv(
-            location.loc,
-            (data.len() / 4) as GLsizei,
-            data.as_ptr() as *const GLint,
-        );
-    }
+pub trait UniformValue {
+    fn uniform_data(&self) -> UniformData<'_>;
+}
 
-    get_rgl_result(())
+#[derive(Clone, Debug, PartialEq)]
+pub enum UniformError {
+    MalformedLength { len: usize, element_len: usize },
+    InvalidDimensions { columns: usize, rows: usize },
+    Standard(Vec<Error>),
 }
 
-pub fn uniform1uiv(location: UniformLocation, data: &[u32]) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform1uiv(
-            location.loc,
-            data.len() as GLsizei,
-            data.as_ptr() as *const GLuint,
-        );
-    }
+////////////////////////////////////////////////////////////////////
 
-    get_rgl_result(())
+macro_rules! impl_uniform_value {
+    ($scalar:ty, $variant:ident) => {
+        impl UniformValue for $scalar {
+            fn uniform_data(&self) -> UniformData<'_> {
+                UniformData::$variant { components: 1, data: std::slice::from_ref(self) }
+            }
+        }
+
+        impl UniformValue for [$scalar] {
+            fn uniform_data(&self) -> UniformData<'_> {
+                UniformData::$variant { components: 1, data: self }
+            }
+        }
+
+        impl_uniform_value!(@vector $scalar, $variant, 2);
+        impl_uniform_value!(@vector $scalar, $variant, 3);
+        impl_uniform_value!(@vector $scalar, $variant, 4);
+    };
+    (@vector $scalar:ty, $variant:ident, $n:expr) => {
+        impl UniformValue for [$scalar; $n] {
+            fn uniform_data(&self) -> UniformData<'_> {
+                UniformData::$variant { components: $n, data: self }
+            }
+        }
+
+        impl UniformValue for [[$scalar; $n]] {
+            fn uniform_data(&self) -> UniformData<'_> {
+                UniformData::$variant { components: $n, data: self.as_flattened() }
+            }
+        }
+    };
 }
 
-pub fn uniform2uiv(location: UniformLocation, data: &[u32]) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform2uiv(
-            location.loc,
-            (data.len() / 2) as GLsizei,
-            data.as_ptr() as *const GLuint,
-        );
-    }
+impl_uniform_value!(f32, Float);
+impl_uniform_value!(i32, Int);
+impl_uniform_value!(u32, UnsignedInt);
+impl_uniform_value!(f64, Double);
 
-    get_rgl_result(())
+// `[[f32; R]; C]` is a matrix of C columns and R rows. Arrays of vectors
+// have to be passed as slices to tell them apart from matrices.
+macro_rules! impl_uniform_matrix {
+    ($scalar:ty, $variant:ident, $(($columns:expr, $rows:expr)),*) => {
+        $(
+            impl UniformValue for [[$scalar; $rows]; $columns] {
+                fn uniform_data(&self) -> UniformData<'_> {
+                    UniformData::$variant {
+                        columns: $columns,
+                        rows: $rows,
+                        transpose: false,
+                        data: self.as_flattened(),
+                    }
+                }
+            }
+
+            impl UniformValue for [[[$scalar; $rows]; $columns]] {
+                fn uniform_data(&self) -> UniformData<'_> {
+                    UniformData::$variant {
+                        columns: $columns,
+                        rows: $rows,
+                        transpose: false,
+                        data: self.as_flattened().as_flattened(),
+                    }
+                }
+            }
+        )*
+    };
 }
 
-pub fn uniform3uiv(location: UniformLocation, data: &[u32]) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform3uiv(
-            location.loc,
-            (data.len() / 3) as GLsizei,
-            data.as_ptr() as *const GLuint,
-        );
-    }
+impl_uniform_matrix!(f32, FloatMatrix, (2, 2), (2, 3), (2, 4), (3, 2), (3, 3), (3, 4), (4, 2), (4, 3), (4, 4));
+impl_uniform_matrix!(f64, DoubleMatrix, (2, 2), (2, 3), (2, 4), (3, 2), (3, 3), (3, 4), (4, 2), (4, 3), (4, 4));
 
-    get_rgl_result(())
+impl<T> UniformValue for Vec<T> where [T]: UniformValue {
+    fn uniform_data(&self) -> UniformData<'_> {
+        self.as_slice().uniform_data()
+    }
 }
 
-pub fn uniform4uiv(location: UniformLocation, data: &[u32]) -> RGLResult<()> {
-    unsafe {
-        gl::Uniform4uiv(
-            location.loc,
-            (data.len() / 4) as GLsizei,
-            data.as_ptr() as *const GLuint,
-        );
+impl<T> UniformValue for &T where T: UniformValue + ?Sized {
+    fn uniform_data(&self) -> UniformData<'_> {
+        (**self).uniform_data()
     }
+}
 
-    get_rgl_result(())
+////////////////////////////////////////////////////////////////////
+
+mod private {
+    pub trait PrivUniformScalar {}
+
+    impl PrivUniformScalar for f32 {}
+    impl PrivUniformScalar for i32 {}
+    impl PrivUniformScalar for u32 {}
+    impl PrivUniformScalar for f64 {}
 }
 
-pub fn uniform_matrix2fv(
-    location: UniformLocation,
-    transpose: bool,
-    data: &[f32],
-) -> RGLResult<()> {
-    unsafe {
-        gl::UniformMatrix2fv(
-            location.loc,
-            (data.len() / (2 * 2)) as GLsizei,
-            transpose as GLboolean,
-            data.as_ptr() as *const GLfloat,
-        );
-    }
+pub trait UniformScalar : private::PrivUniformScalar + Sized {
+    fn vectors(components: usize, data: &[Self]) -> UniformData<'_>;
+}
 
-    get_rgl_result(())
+impl UniformScalar for f32 {
+    fn vectors(components: usize, data: &[Self]) -> UniformData<'_> {
+        UniformData::Float { components, data }
+    }
 }
 
-pub fn uniform_matrix3fv(
-    location: UniformLocation,
-    transpose: bool,
-    data: &[f32],
-) -> RGLResult<()> {
-    unsafe {
-        gl::UniformMatrix3fv(
-            location.loc,
-            (data.len() / (3 * 3)) as GLsizei,
-            transpose as GLboolean,
-            data.as_ptr() as *const GLfloat,
-        );
+impl UniformScalar for i32 {
+    fn vectors(components: usize, data: &[Self]) -> UniformData<'_> {
+        UniformData::Int { components, data }
     }
+}
 
-    get_rgl_result(())
+impl UniformScalar for u32 {
+    fn vectors(components: usize, data: &[Self]) -> UniformData<'_> {
+        UniformData::UnsignedInt { components, data }
+    }
 }
 
-pub fn uniform_matrix4fv(
-    location: UniformLocation,
-    transpose: bool,
-    data: &[f32],
-) -> RGLResult<()> {
-    unsafe {
-        gl::UniformMatrix4fv(
-            location.loc,
-            (data.len() / (4 * 4)) as GLsizei,
-            transpose as GLboolean,
-            data.as_ptr() as *const GLfloat,
-        );
+impl UniformScalar for f64 {
+    fn vectors(components: usize, data: &[Self]) -> UniformData<'_> {
+        UniformData::Double { components, data }
     }
+}
 
-    get_rgl_result(())
+pub trait UniformMatrixScalar : UniformScalar {
+    fn matrices(columns: usize, rows: usize, data: &[Self]) -> UniformData<'_>;
 }
 
-pub fn uniform_matrix2x3fv(
-    location: UniformLocation,
-    transpose: bool,
-    data: &[f32],
-) -> RGLResult<()> {
-    unsafe {
-        gl::UniformMatrix2x3fv(
-            location.loc,
-            (data.len() / (2 * 3)) as GLsizei,
-            transpose as GLboolean,
-            data.as_ptr() as *const GLfloat,
-        );
+impl UniformMatrixScalar for f32 {
+    fn matrices(columns: usize, rows: usize, data: &[Self]) -> UniformData<'_> {
+        UniformData::FloatMatrix { columns, rows, transpose: false, data }
     }
-
-    get_rgl_result(())
 }
 
-pub fn uniform_matrix3x2fv(
-    location: UniformLocation,
-    transpose: bool,
-    data: &[f32],
-) -> RGLResult<()> {
-    unsafe {
-        gl::UniformMatrix3x2fv(
-            location.loc,
-            (data.len() / (3 * 2)) as GLsizei,
-            transpose as GLboolean,
-            data.as_ptr() as *const GLfloat,
-        );
+impl UniformMatrixScalar for f64 {
+    fn matrices(columns: usize, rows: usize, data: &[Self]) -> UniformData<'_> {
+        UniformData::DoubleMatrix { columns, rows, transpose: false, data }
     }
+}
 
-    get_rgl_result(())
+/// A flat slice uploaded as an array of `V` sized vectors. Its length has to
+/// be a multiple of the vector size.
+#[derive(Copy, Clone, Debug)]
+pub struct FlatVectors<'a, V, T> where V: AttributeVector, T: UniformScalar {
+    data: &'a [T],
+    vector_marker: PhantomData<V>,
 }
 
-pub fn uniform_matrix2x4fv(
-    location: UniformLocation,
-    transpose: bool,
-    data: &[f32],
-) -> RGLResult<()> {
-    unsafe {
-        gl::UniformMatrix2x4fv(
-            location.loc,
-            (data.len() / (2 * 4)) as GLsizei,
-            transpose as GLboolean,
-            data.as_ptr() as *const GLfloat,
-        );
+impl<'a, V, T> FlatVectors<'a, V, T> where V: AttributeVector, T: UniformScalar {
+    pub fn new(data: &'a [T]) -> FlatVectors<'a, V, T> {
+        FlatVectors {
+            data,
+            vector_marker: PhantomData,
+        }
     }
-
-    get_rgl_result(())
 }
 
-pub fn uniform_matrix4x2fv(
-    location: UniformLocation,
-    transpose: bool,
-    data: &[f32],
-) -> RGLResult<()> {
-    unsafe {
-        gl::UniformMatrix4x2fv(
-            location.loc,
-            (data.len() / (4 * 2)) as GLsizei,
-            transpose as GLboolean,
-            data.as_ptr() as *const GLfloat,
-        );
+impl<'a, V, T> UniformValue for FlatVectors<'a, V, T> where V: AttributeVector, T: UniformScalar {
+    fn uniform_data(&self) -> UniformData<'_> {
+        T::vectors(V::len() as usize, self.data)
     }
+}
 
-    get_rgl_result(())
+/// A flat, column major slice uploaded as an array of `columns` x `rows`
+/// matrices. Its length has to be a multiple of the matrix size.
+#[derive(Copy, Clone, Debug)]
+pub struct FlatMatrices<'a, T = f32> where T: UniformMatrixScalar {
+    pub columns: usize,
+    pub rows: usize,
+    pub data: &'a [T],
 }
 
-pub fn uniform_matrix3x4fv(
-    location: UniformLocation,
-    transpose: bool,
-    data: &[f32],
-) -> RGLResult<()> {
-    unsafe {
-        gl::UniformMatrix3x4fv(
-            location.loc,
-            (data.len() / (3 * 4)) as GLsizei,
-            transpose as GLboolean,
-            data.as_ptr() as *const GLfloat,
-        );
+impl<'a, T> UniformValue for FlatMatrices<'a, T> where T: UniformMatrixScalar {
+    fn uniform_data(&self) -> UniformData<'_> {
+        T::matrices(self.columns, self.rows, self.data)
     }
-
-    get_rgl_result(())
 }
 
-pub fn uniform_matrix4x3fv(
-    location: UniformLocation,
-    transpose: bool,
-    data: &[f32],
-) -> RGLResult<()> {
-    unsafe {
-        gl::UniformMatrix4x3fv(
-            location.loc,
-            (data.len() / (4 * 3)) as GLsizei,
-            transpose as GLboolean,
-            data.as_ptr() as *const GLfloat,
-        );
+/// Uploads a matrix value with GL transposing it, for row major data.
+#[derive(Copy, Clone, Debug)]
+pub struct Transposed<T>(pub T);
+
+impl<T> UniformValue for Transposed<T> where T: UniformValue {
+    fn uniform_data(&self) -> UniformData<'_> {
+        match self.0.uniform_data() {
+            UniformData::FloatMatrix { columns, rows, transpose, data } => UniformData::FloatMatrix {
+                columns,
+                rows,
+                transpose: !transpose,
+                data,
+            },
+            UniformData::DoubleMatrix { columns, rows, transpose, data } => UniformData::DoubleMatrix {
+                columns,
+                rows,
+                transpose: !transpose,
+                data,
+            },
+            other => other,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+
+fn element_count(len: usize, element_len: usize) -> Result<GLsizei, UniformError> {
+    if len == 0 || element_len == 0 || !len.is_multiple_of(element_len) {
+        Err(UniformError::MalformedLength { len, element_len })
+    } else {
+        Ok((len / element_len) as GLsizei)
+    }
+}
+
+fn check_dimensions(columns: usize, rows: usize) -> Result<(), UniformError> {
+    if (2..=4).contains(&columns) && (2..=4).contains(&rows) {
+        Ok(())
+    } else {
+        Err(UniformError::InvalidDimensions { columns, rows })
+    }
+}
+
+// Calls the `glProgramUniform*` variant when a program is given, the
+// `glUniform*` one for the current program otherwise.
+macro_rules! uniform_call {
+    ($program:expr, $uniform:ident, $program_uniform:ident, $($arg:expr),*) => {
+        match $program {
+            Some(program_id) => gl::$program_uniform(program_id, $($arg),*),
+            None => gl::$uniform($($arg),*),
+        }
+    };
+}
+
+fn upload_uniform(program: Option<GLuint>, location: UniformLocation, data: UniformData<'_>) -> Result<(), UniformError> {
+    let loc = location.loc;
+
+    match data {
+        UniformData::Float { components, data } => {
+            let count = element_count(data.len(), components)?;
+            let ptr = data.as_ptr();
+
+            unsafe {
+                match components {
+                    1 => uniform_call!(program, Uniform1fv, ProgramUniform1fv, loc, count, ptr),
+                    2 => uniform_call!(program, Uniform2fv, ProgramUniform2fv, loc, count, ptr),
+                    3 => uniform_call!(program, Uniform3fv, ProgramUniform3fv, loc, count, ptr),
+                    4 => uniform_call!(program, Uniform4fv, ProgramUniform4fv, loc, count, ptr),
+                    _ => return Err(UniformError::InvalidDimensions { columns: 1, rows: components }),
+                }
+            }
+        }
+        UniformData::Int { components, data } => {
+            let count = element_count(data.len(), components)?;
+            let ptr = data.as_ptr();
+
+            unsafe {
+                match components {
+                    1 => uniform_call!(program, Uniform1iv, ProgramUniform1iv, loc, count, ptr),
+                    2 => uniform_call!(program, Uniform2iv, ProgramUniform2iv, loc, count, ptr),
+                    3 => uniform_call!(program, Uniform3iv, ProgramUniform3iv, loc, count, ptr),
+                    4 => uniform_call!(program, Uniform4iv, ProgramUniform4iv, loc, count, ptr),
+                    _ => return Err(UniformError::InvalidDimensions { columns: 1, rows: components }),
+                }
+            }
+        }
+        UniformData::UnsignedInt { components, data } => {
+            let count = element_count(data.len(), components)?;
+            let ptr = data.as_ptr();
+
+            unsafe {
+                match components {
+                    1 => uniform_call!(program, Uniform1uiv, ProgramUniform1uiv, loc, count, ptr),
+                    2 => uniform_call!(program, Uniform2uiv, ProgramUniform2uiv, loc, count, ptr),
+                    3 => uniform_call!(program, Uniform3uiv, ProgramUniform3uiv, loc, count, ptr),
+                    4 => uniform_call!(program, Uniform4uiv, ProgramUniform4uiv, loc, count, ptr),
+                    _ => return Err(UniformError::InvalidDimensions { columns: 1, rows: components }),
+                }
+            }
+        }
+        UniformData::Double { components, data } => {
+            let count = element_count(data.len(), components)?;
+            let ptr = data.as_ptr();
+
+            unsafe {
+                match components {
+                    1 => uniform_call!(program, Uniform1dv, ProgramUniform1dv, loc, count, ptr),
+                    2 => uniform_call!(program, Uniform2dv, ProgramUniform2dv, loc, count, ptr),
+                    3 => uniform_call!(program, Uniform3dv, ProgramUniform3dv, loc, count, ptr),
+                    4 => uniform_call!(program, Uniform4dv, ProgramUniform4dv, loc, count, ptr),
+                    _ => return Err(UniformError::InvalidDimensions { columns: 1, rows: components }),
+                }
+            }
+        }
+        UniformData::FloatMatrix { columns, rows, transpose, data } => {
+            check_dimensions(columns, rows)?;
+
+            let count = element_count(data.len(), columns * rows)?;
+            let transpose = transpose as GLboolean;
+            let ptr = data.as_ptr();
+
+            unsafe {
+                match (columns, rows) {
+                    (2, 2) => uniform_call!(program, UniformMatrix2fv, ProgramUniformMatrix2fv, loc, count, transpose, ptr),
+                    (2, 3) => uniform_call!(program, UniformMatrix2x3fv, ProgramUniformMatrix2x3fv, loc, count, transpose, ptr),
+                    (2, 4) => uniform_call!(program, UniformMatrix2x4fv, ProgramUniformMatrix2x4fv, loc, count, transpose, ptr),
+                    (3, 2) => uniform_call!(program, UniformMatrix3x2fv, ProgramUniformMatrix3x2fv, loc, count, transpose, ptr),
+                    (3, 3) => uniform_call!(program, UniformMatrix3fv, ProgramUniformMatrix3fv, loc, count, transpose, ptr),
+                    (3, 4) => uniform_call!(program, UniformMatrix3x4fv, ProgramUniformMatrix3x4fv, loc, count, transpose, ptr),
+                    (4, 2) => uniform_call!(program, UniformMatrix4x2fv, ProgramUniformMatrix4x2fv, loc, count, transpose, ptr),
+                    (4, 3) => uniform_call!(program, UniformMatrix4x3fv, ProgramUniformMatrix4x3fv, loc, count, transpose, ptr),
+                    _ => uniform_call!(program, UniformMatrix4fv, ProgramUniformMatrix4fv, loc, count, transpose, ptr),
+                }
+            }
+        }
+        UniformData::DoubleMatrix { columns, rows, transpose, data } => {
+            check_dimensions(columns, rows)?;
+
+            let count = element_count(data.len(), columns * rows)?;
+            let transpose = transpose as GLboolean;
+            let ptr = data.as_ptr();
+
+            unsafe {
+                match (columns, rows) {
+                    (2, 2) => uniform_call!(program, UniformMatrix2dv, ProgramUniformMatrix2dv, loc, count, transpose, ptr),
+                    (2, 3) => uniform_call!(program, UniformMatrix2x3dv, ProgramUniformMatrix2x3dv, loc, count, transpose, ptr),
+                    (2, 4) => uniform_call!(program, UniformMatrix2x4dv, ProgramUniformMatrix2x4dv, loc, count, transpose, ptr),
+                    (3, 2) => uniform_call!(program, UniformMatrix3x2dv, ProgramUniformMatrix3x2dv, loc, count, transpose, ptr),
+                    (3, 3) => uniform_call!(program, UniformMatrix3dv, ProgramUniformMatrix3dv, loc, count, transpose, ptr),
+                    (3, 4) => uniform_call!(program, UniformMatrix3x4dv, ProgramUniformMatrix3x4dv, loc, count, transpose, ptr),
+                    (4, 2) => uniform_call!(program, UniformMatrix4x2dv, ProgramUniformMatrix4x2dv, loc, count, transpose, ptr),
+                    (4, 3) => uniform_call!(program, UniformMatrix4x3dv, ProgramUniformMatrix4x3dv, loc, count, transpose, ptr),
+                    _ => uniform_call!(program, UniformMatrix4dv, ProgramUniformMatrix4dv, loc, count, transpose, ptr),
+                }
+            }
+        }
+    }
+
+    get_rgl_result(()).map_err(UniformError::Standard)
+}
+
+/// Sets a uniform of the current program, picking the `glUniform*` call from
+/// the type of `value`.
+pub fn set_uniform<V>(location: UniformLocation, value: &V) -> Result<(), UniformError> where V: UniformValue + ?Sized {
+    upload_uniform(None, location, value.uniform_data())
+}
+
+/// Sets a uniform of `program` with `glProgramUniform*`, without binding it.
+pub fn program_uniform<V>(program: &ShaderProgram, location: UniformLocation, value: &V) -> Result<(), UniformError> where V: UniformValue + ?Sized {
+    upload_uniform(Some(program.program_id), location, value.uniform_data())
+}
+
+////////////////////////////////////////////////////////////////////
+
+/// Types a uniform's current value can be read back as.
+pub trait UniformReadable : Sized {
+    /// Whether a uniform of `glsl_type` has exactly the components of `Self`.
+    fn accepts(glsl_type: GlslType) -> bool;
+
+    fn get_uniform(program: &ShaderProgram, location: UniformLocation) -> RGLResult<Self>;
+}
+
+type GetUniformFn<S> = unsafe fn(GLuint, GLint, *mut S);
+
+// Array uniforms occupy consecutive locations starting at their base.
+fn uniform_type_at(program: &ShaderProgram, location: UniformLocation) -> RGLResult<Option<GlslType>> {
+    let uniforms = crate::get_active_uniforms(program)?;
+
+    Ok(uniforms
+        .iter()
+        .find(|u| u.location >= 0 && (u.location..u.location + u.size.max(1)).contains(&location.loc))
+        .map(|u| u.glsl_type))
+}
+
+// glGetUniform*v writes as many components as the uniform has, so the type
+// is checked first to keep it from writing past `data`.
+fn get_uniform_components<V, S>(program: &ShaderProgram, location: UniformLocation, get: GetUniformFn<S>, data: &mut [S]) -> RGLResult<()> where V: UniformReadable {
+    match uniform_type_at(program, location)? {
+        Some(glsl_type) if V::accepts(glsl_type) => {}
+        _ => return Err(vec![Error::InvalidOperation]),
+    }
+
+    unsafe {
+        get(program.program_id, location.loc, data.as_mut_ptr());
+    }
+
+    get_rgl_result(())
+}
+
+macro_rules! impl_uniform_readable {
+    ($value:ty, $get:ident, $flatten:ident, $($glsl:ident)|+) => {
+        impl UniformReadable for $value {
+            fn accepts(glsl_type: GlslType) -> bool {
+                matches!(glsl_type, $(GlslType::$glsl)|+)
+            }
+
+            fn get_uniform(program: &ShaderProgram, location: UniformLocation) -> RGLResult<Self> {
+                let mut value: $value = Default::default();
+                get_uniform_components::<Self, _>(program, location, gl::$get, value.$flatten())?;
+                Ok(value)
+            }
+        }
+    };
+}
+
+macro_rules! impl_uniform_readable_scalar {
+    ($scalar:ty, $get:ident, $($glsl:ident)|+) => {
+        impl UniformReadable for $scalar {
+            fn accepts(glsl_type: GlslType) -> bool {
+                matches!(glsl_type, $(GlslType::$glsl)|+)
+            }
+
+            fn get_uniform(program: &ShaderProgram, location: UniformLocation) -> RGLResult<Self> {
+                let mut value: [$scalar; 1] = Default::default();
+                get_uniform_components::<Self, _>(program, location, gl::$get, &mut value)?;
+                Ok(value[0])
+            }
+        }
+    };
+}
+
+impl_uniform_readable_scalar!(f32, GetUniformfv, Float);
+impl_uniform_readable_scalar!(f64, GetUniformdv, Double);
+impl_uniform_readable_scalar!(u32, GetUniformuiv, UnsignedInt | AtomicUnsignedInt);
+// Sampler and image uniforms are read as the unit they are bound to.
+impl UniformReadable for i32 {
+    fn accepts(glsl_type: GlslType) -> bool {
+        glsl_type == GlslType::Int || glsl_type.is_sampler_or_image()
+    }
+
+    fn get_uniform(program: &ShaderProgram, location: UniformLocation) -> RGLResult<Self> {
+        let mut value = [0];
+        get_uniform_components::<Self, _>(program, location, gl::GetUniformiv, &mut value)?;
+        Ok(value[0])
     }
+}
 
-    get_rgl_result(())
+impl_uniform_readable!([f32; 2], GetUniformfv, as_mut_slice, FloatVec2);
+impl_uniform_readable!([f32; 3], GetUniformfv, as_mut_slice, FloatVec3);
+impl_uniform_readable!([f32; 4], GetUniformfv, as_mut_slice, FloatVec4);
+impl_uniform_readable!([f64; 2], GetUniformdv, as_mut_slice, DoubleVec2);
+impl_uniform_readable!([f64; 3], GetUniformdv, as_mut_slice, DoubleVec3);
+impl_uniform_readable!([f64; 4], GetUniformdv, as_mut_slice, DoubleVec4);
+impl_uniform_readable!([i32; 2], GetUniformiv, as_mut_slice, IntVec2);
+impl_uniform_readable!([i32; 3], GetUniformiv, as_mut_slice, IntVec3);
+impl_uniform_readable!([i32; 4], GetUniformiv, as_mut_slice, IntVec4);
+impl_uniform_readable!([u32; 2], GetUniformuiv, as_mut_slice, UnsignedIntVec2);
+impl_uniform_readable!([u32; 3], GetUniformuiv, as_mut_slice, UnsignedIntVec3);
+impl_uniform_readable!([u32; 4], GetUniformuiv, as_mut_slice, UnsignedIntVec4);
+
+impl_uniform_readable!([[f32; 2]; 2], GetUniformfv, as_flattened_mut, FloatMat2);
+impl_uniform_readable!([[f32; 3]; 2], GetUniformfv, as_flattened_mut, FloatMat2x3);
+impl_uniform_readable!([[f32; 4]; 2], GetUniformfv, as_flattened_mut, FloatMat2x4);
+impl_uniform_readable!([[f32; 2]; 3], GetUniformfv, as_flattened_mut, FloatMat3x2);
+impl_uniform_readable!([[f32; 3]; 3], GetUniformfv, as_flattened_mut, FloatMat3);
+impl_uniform_readable!([[f32; 4]; 3], GetUniformfv, as_flattened_mut, FloatMat3x4);
+impl_uniform_readable!([[f32; 2]; 4], GetUniformfv, as_flattened_mut, FloatMat4x2);
+impl_uniform_readable!([[f32; 3]; 4], GetUniformfv, as_flattened_mut, FloatMat4x3);
+impl_uniform_readable!([[f32; 4]; 4], GetUniformfv, as_flattened_mut, FloatMat4);
+impl_uniform_readable!([[f64; 2]; 2], GetUniformdv, as_flattened_mut, DoubleMat2);
+impl_uniform_readable!([[f64; 3]; 2], GetUniformdv, as_flattened_mut, DoubleMat2x3);
+impl_uniform_readable!([[f64; 4]; 2], GetUniformdv, as_flattened_mut, DoubleMat2x4);
+impl_uniform_readable!([[f64; 2]; 3], GetUniformdv, as_flattened_mut, DoubleMat3x2);
+impl_uniform_readable!([[f64; 3]; 3], GetUniformdv, as_flattened_mut, DoubleMat3);
+impl_uniform_readable!([[f64; 4]; 3], GetUniformdv, as_flattened_mut, DoubleMat3x4);
+impl_uniform_readable!([[f64; 2]; 4], GetUniformdv, as_flattened_mut, DoubleMat4x2);
+impl_uniform_readable!([[f64; 3]; 4], GetUniformdv, as_flattened_mut, DoubleMat4x3);
+impl_uniform_readable!([[f64; 4]; 4], GetUniformdv, as_flattened_mut, DoubleMat4);
+
+macro_rules! impl_uniform_readable_bool {
+    ($value:ty, $n:expr, $glsl:ident, |$components:ident| $convert:expr) => {
+        impl UniformReadable for $value {
+            fn accepts(glsl_type: GlslType) -> bool {
+                glsl_type == GlslType::$glsl
+            }
+
+            fn get_uniform(program: &ShaderProgram, location: UniformLocation) -> RGLResult<Self> {
+                let mut $components = [0; $n];
+                get_uniform_components::<Self, _>(program, location, gl::GetUniformiv, &mut $components)?;
+                Ok($convert)
+            }
+        }
+    };
+}
+
+impl_uniform_readable_bool!(bool, 1, Bool, |value| value[0] != 0);
+impl_uniform_readable_bool!([bool; 2], 2, BoolVec2, |value| value.map(|c| c != 0));
+impl_uniform_readable_bool!([bool; 3], 3, BoolVec3, |value| value.map(|c| c != 0));
+impl_uniform_readable_bool!([bool; 4], 4, BoolVec4, |value| value.map(|c| c != 0));
+
+/// The current value of a uniform of `program`, for checking what was
+/// uploaded. Matrices are returned column major. Fails with
+/// `InvalidOperation` if the active uniform at `location` is not of a type
+/// `V` can hold.
+///
+/// The type is looked up by enumerating every active uniform of `program`,
+/// so each call costs a query per active uniform; this is meant for tests
+/// and debugging, not per-frame use.
+pub fn get_uniform<V>(program: &ShaderProgram, location: UniformLocation) -> RGLResult<V> where V: UniformReadable {
+    V::get_uniform(program, location)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_array_and_struct_paths() {
+        assert_eq!(normalize_uniform_path("lights[3].color").as_deref(), Some("lights[3].color"));
+        assert_eq!(normalize_uniform_path(" lights [ 03 ] . color ").as_deref(), Some("lights[3].color"));
+        assert_eq!(normalize_uniform_path("s.t[2].u").as_deref(), Some("s.t[2].u"));
+    }
+
+    #[test]
+    fn normalizes_trailing_zero_index() {
+        assert_eq!(normalize_uniform_path("weights[0]").as_deref(), Some("weights"));
+        assert_eq!(normalize_uniform_path("grid[2][0]").as_deref(), Some("grid[2]"));
+        assert_eq!(normalize_uniform_path("lights[0].color").as_deref(), Some("lights[0].color"));
+    }
+
+    #[test]
+    fn rejects_malformed_paths() {
+        assert_eq!(normalize_uniform_path(""), None);
+        assert_eq!(normalize_uniform_path("1x"), None);
+        assert_eq!(normalize_uniform_path("bad[x]"), None);
+        assert_eq!(normalize_uniform_path("open[1"), None);
+        assert_eq!(normalize_uniform_path("m[1]x"), None);
+        assert_eq!(normalize_uniform_path("a..b"), None);
+    }
+
+    fn candidates(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn suggests_close_matches_by_distance() {
+        let active = candidates(&["mvp", "model", "lights[0].color", "lights[0].power"]);
+
+        assert_eq!(close_matches("mpv", &active), candidates(&["mvp"]));
+        assert_eq!(close_matches("lights[3].colr", &active), candidates(&["lights[0].color", "lights[0].power"]));
+    }
+
+    #[test]
+    fn suggests_nothing_for_distant_names() {
+        let active = candidates(&["mvp", "model"]);
+
+        assert!(close_matches("resolution", &active).is_empty());
+    }
+
+    #[test]
+    fn limits_suggestions_to_three() {
+        let active = candidates(&["ab", "ac", "ad", "ae"]);
+
+        assert_eq!(close_matches("aa", &active).len(), 3);
+    }
 }
@@ -0,0 +1,245 @@
+// A lexical scan of GLSL sources for `uniform` declarations, used to tell
+// uniforms the linker optimized out from names that were never declared.
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct DeclaredUniform {
+    pub(crate) name: String,
+    pub(crate) block: Option<String>,
+}
+
+const GLSL_QUALIFIERS: &[&str] = &[
+    "lowp", "mediump", "highp", "precise", "invariant", "row_major", "column_major",
+    "shared", "packed", "std140", "std430", "coherent", "volatile", "restrict",
+    "readonly", "writeonly",
+];
+
+// Comments and preprocessor lines removed, split into identifiers, numbers
+// and single punctuation characters.
+fn glsl_tokens(source: &str) -> Vec<String> {
+    let mut stripped = String::with_capacity(source.len());
+    let mut rest = source;
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("//") {
+            rest = &after[after.find('\n').unwrap_or(after.len())..];
+        } else if let Some(after) = rest.strip_prefix("/*") {
+            let end = after.find("*/").map_or(after.len(), |e| e + 2);
+            stripped.extend(after[..end].chars().filter(|c| *c == '\n'));
+            stripped.push(' ');
+            rest = &after[end..];
+        } else {
+            let c = rest.chars().next().unwrap();
+            stripped.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    let mut tokens = vec![];
+
+    for line in stripped.lines().filter(|l| !l.trim_start().starts_with('#')) {
+        let mut chars = line.char_indices().peekable();
+
+        while let Some((start, c)) = chars.next() {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                let mut end = start + 1;
+                while let Some((i, _)) = chars.next_if(|(_, c)| c.is_ascii_alphanumeric() || *c == '_' || *c == '.') {
+                    end = i + 1;
+                }
+                tokens.push(line[start..end].to_owned());
+            } else if !c.is_whitespace() {
+                tokens.push(c.to_string());
+            }
+        }
+    }
+
+    tokens
+}
+
+fn strip_qualifiers(tokens: &[String]) -> Vec<&str> {
+    let mut stripped = vec![];
+    let mut i = 0;
+
+    while i < tokens.len() {
+        if tokens[i] == "layout" {
+            let close = tokens[i..].iter().position(|t| t == ")").unwrap_or(tokens.len() - i - 1);
+            i += close + 1;
+        } else {
+            if !GLSL_QUALIFIERS.contains(&tokens[i].as_str()) {
+                stripped.push(tokens[i].as_str());
+            }
+            i += 1;
+        }
+    }
+
+    stripped
+}
+
+// The names in `a, b[2], c = 1.0` following a declaration's type.
+fn declarator_names(tokens: &[&str]) -> Vec<String> {
+    let mut names = vec![];
+    let mut depth = 0;
+    let mut expect_name = true;
+
+    for token in tokens {
+        match *token {
+            "(" | "[" => depth += 1,
+            ")" | "]" => depth -= 1,
+            "," if depth == 0 => expect_name = true,
+            name if expect_name && depth == 0 => {
+                names.push(name.to_owned());
+                expect_name = false;
+            }
+            _ => {}
+        }
+    }
+
+    names
+}
+
+fn scan_uniform_statement(tokens: &[String], declared: &mut Vec<DeclaredUniform>) {
+    let statement = strip_qualifiers(tokens);
+
+    let close = statement.iter().rposition(|t| *t == "}");
+
+    match (statement.first(), statement.get(1), statement.get(2), close) {
+        (Some(&"struct"), _, _, Some(close)) => {
+            for name in declarator_names(&statement[close + 1..]) {
+                declared.push(DeclaredUniform { name, block: None });
+            }
+        }
+        (Some(block), Some(&"{"), _, Some(close)) => {
+            let block = block.to_string();
+            let members = &statement[2..close];
+
+            let mut names = declarator_names(&statement[close + 1..]);
+            names.push(block.clone());
+
+            for member in members.split(|t| *t == ";").filter(|m| !m.is_empty()) {
+                names.extend(declarator_names(&member[1..]));
+            }
+
+            for name in names {
+                declared.push(DeclaredUniform { name, block: Some(block.clone()) });
+            }
+        }
+        (Some(_), Some(_), _, None) => {
+            for name in declarator_names(&statement[1..]) {
+                declared.push(DeclaredUniform { name, block: None });
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The uniforms declared in a GLSL source, found with a lexical scan of
+/// top level `uniform` statements.
+pub(crate) fn scan_declared_uniforms(source: &str) -> Vec<DeclaredUniform> {
+    let tokens = glsl_tokens(source);
+    let mut declared = vec![];
+    let mut depth = 0;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        match tokens[i].as_str() {
+            "{" => depth += 1,
+            "}" => depth -= 1,
+            "uniform" if depth == 0 => {
+                let mut inner_depth = 0;
+                let mut end = i + 1;
+
+                while end < tokens.len() && !(tokens[end] == ";" && inner_depth == 0) {
+                    match tokens[end].as_str() {
+                        "{" => inner_depth += 1,
+                        "}" => inner_depth -= 1,
+                        _ => {}
+                    }
+                    end += 1;
+                }
+
+                scan_uniform_statement(&tokens[i + 1..end], &mut declared);
+                i = end;
+            }
+            _ => {}
+        }
+
+        i += 1;
+    }
+
+    declared
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(source: &str) -> Vec<(String, Option<String>)> {
+        scan_declared_uniforms(source).into_iter().map(|d| (d.name, d.block)).collect()
+    }
+
+    fn declared(names: &[(&str, Option<&str>)]) -> Vec<(String, Option<String>)> {
+        names.iter().map(|(name, block)| (name.to_string(), block.map(str::to_owned))).collect()
+    }
+
+    #[test]
+    fn scans_multiple_declarators() {
+        assert_eq!(
+            scan("uniform vec3 a, b[2];\nuniform float c = 1.0, d;"),
+            declared(&[("a", None), ("b", None), ("c", None), ("d", None)])
+        );
+    }
+
+    #[test]
+    fn skips_layout_and_precision_qualifiers() {
+        assert_eq!(
+            scan("layout(location = 3) uniform highp mat4 mvp;\nlayout(binding = 0) uniform sampler2D tex;"),
+            declared(&[("mvp", None), ("tex", None)])
+        );
+    }
+
+    #[test]
+    fn skips_comments_and_preprocessor_lines() {
+        let source = "#version 330 core\n\
+                      #define uniform_count 2\n\
+                      // uniform float line_comment;\n\
+                      /* uniform float block_comment;\n\
+                         uniform float still_comment; */\n\
+                      uniform float real;";
+
+        assert_eq!(scan(source), declared(&[("real", None)]));
+    }
+
+    #[test]
+    fn scans_blocks_with_instance_names() {
+        let source = "layout(std140) uniform Matrices { layout(row_major) mat4 proj; mat4 view[2]; } m;";
+
+        assert_eq!(
+            scan(source),
+            declared(&[
+                ("m", Some("Matrices")),
+                ("Matrices", Some("Matrices")),
+                ("proj", Some("Matrices")),
+                ("view", Some("Matrices")),
+            ])
+        );
+    }
+
+    #[test]
+    fn scans_blocks_without_instance_names() {
+        assert_eq!(
+            scan("uniform Globals { float time; vec2 resolution; };"),
+            declared(&[("Globals", Some("Globals")), ("time", Some("Globals")), ("resolution", Some("Globals"))])
+        );
+    }
+
+    #[test]
+    fn scans_struct_uniforms() {
+        let source = "struct Light { vec3 color; };\nuniform Light lights[4];\nuniform struct Fog { float d; } fog;";
+
+        assert_eq!(scan(source), declared(&[("lights", None), ("fog", None)]));
+    }
+
+    #[test]
+    fn ignores_uniform_inside_function_bodies() {
+        assert_eq!(scan("void main() { float uniformity = 1.0; }"), declared(&[]));
+    }
+}
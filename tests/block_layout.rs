@@ -0,0 +1,97 @@
+use rgl::{std140_bytes, std430_bytes, LayoutMember, Scalar, Std140, Std430};
+
+#[derive(Copy, Clone, Debug, PartialEq, rgl_macros::Std140, rgl_macros::Std430)]
+struct Light {
+    position: [f32; 3],
+    intensity: f32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, rgl_macros::Std140, rgl_macros::Std430)]
+struct Scene {
+    ambient: f32,
+    lights: [Light; 2],
+    count: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, rgl_macros::Std140, rgl_macros::Std430)]
+struct Particle {
+    weights: [Scalar<f32>; 3],
+    mass: f32,
+    velocity: [f64; 3],
+}
+
+fn member(name: &str, offset: usize, array_stride: Option<usize>) -> LayoutMember {
+    LayoutMember {
+        name: name.to_owned(),
+        offset,
+        array_stride,
+    }
+}
+
+#[test]
+fn vec3_and_float_share_a_slot() {
+    assert_eq!((<Light as Std140>::ALIGNMENT, <Light as Std140>::SIZE), (16, 16));
+    assert_eq!((<Light as Std430>::ALIGNMENT, <Light as Std430>::SIZE), (16, 16));
+
+    let mut members = vec![];
+    <Light as Std140>::std140_members("", 0, &mut members);
+
+    assert_eq!(members, vec![member("position", 0, None), member("intensity", 12, None)]);
+}
+
+#[test]
+fn struct_arrays() {
+    assert_eq!(<Scene as Std140>::SIZE, 64);
+
+    let mut members = vec![];
+    <Scene as Std140>::std140_members("", 0, &mut members);
+
+    assert_eq!(
+        members,
+        vec![
+            member("ambient", 0, None),
+            member("lights[0].position", 16, None),
+            member("lights[0].intensity", 28, None),
+            member("count", 48, None),
+        ]
+    );
+}
+
+#[test]
+fn std430_packs_scalar_arrays() {
+    assert_eq!((<Particle as Std140>::ALIGNMENT, <Particle as Std140>::SIZE), (32, 96));
+    assert_eq!((<Particle as Std430>::ALIGNMENT, <Particle as Std430>::SIZE), (32, 64));
+
+    let mut members = vec![];
+    <Particle as Std430>::std430_members("p", 0, &mut members);
+
+    assert_eq!(
+        members,
+        vec![member("p.weights", 0, Some(4)), member("p.mass", 12, None), member("p.velocity", 32, None)]
+    );
+}
+
+#[test]
+fn derived_bytes_round_trip() {
+    let scene = Scene {
+        ambient: 0.25,
+        lights: [
+            Light { position: [1.0, 2.0, 3.0], intensity: 4.0 },
+            Light { position: [5.0, 6.0, 7.0], intensity: 8.0 },
+        ],
+        count: 2,
+    };
+
+    let bytes = std140_bytes(&scene);
+    assert_eq!(&bytes[44..48], &8.0f32.to_ne_bytes());
+    assert_eq!(&bytes[48..52], &2u32.to_ne_bytes());
+    assert_eq!(Scene::read_std140(&bytes), scene);
+
+    let particle = Particle {
+        weights: [Scalar(0.5), Scalar(0.25), Scalar(0.125)],
+        mass: 2.0,
+        velocity: [1.0, -1.0, 0.5],
+    };
+
+    assert_eq!(Particle::read_std430(&std430_bytes(&particle)), particle);
+}